@@ -0,0 +1,179 @@
+// Runtime configuration, read once from environment variables
+
+use std::{env, str::FromStr, sync::OnceLock};
+
+const DEFAULT_ODESLI_API_URL: &str = "https://api.song.link/v1-alpha.1";
+
+const DEFAULT_MAX_LINKS_PER_RESPONSE: usize = 30;
+
+const DEFAULT_MAX_LINKS_PER_INVOCATION: usize = 10;
+
+/// Default number of times per minute that the bot can be used by each user, in each channel
+/// and in each guild.
+const DEFAULT_USER_RATE_LIMIT: u32 = 5;
+const DEFAULT_CHANNEL_RATE_LIMIT: u32 = 15;
+const DEFAULT_GUILD_RATE_LIMIT: u32 = 40;
+
+const DEFAULT_SETTINGS_PATH: &str = "settings.json";
+
+const DEFAULT_WAYBACK_API_URL: &str = "https://archive.org";
+
+const DEFAULT_NEWS_DOMAINS: &[&str] = &[
+    "nytimes.com",
+    "washingtonpost.com",
+    "wsj.com",
+    "ft.com",
+    "bloomberg.com",
+    "economist.com",
+    "theatlantic.com",
+    "newyorker.com",
+    "wired.com",
+    "businessinsider.com",
+    "telegraph.co.uk",
+    "thetimes.co.uk",
+    "lemonde.fr",
+    "publico.pt",
+    "expresso.pt",
+];
+
+const DEFAULT_FEDIVERSE_INSTANCES: &[&str] = &[
+    "mastodon.social",
+    "mastodon.online",
+    "mstdn.social",
+    "fosstodon.org",
+    "hachyderm.io",
+    "infosec.exchange",
+];
+
+/// Hosts of the privacy frontends used for text-heavy sites, tweets and YouTube videos.
+pub struct Frontends {
+    pub medium: String,
+    pub fandom: String,
+    pub quora: String,
+    pub genius: String,
+    pub stackoverflow: String,
+    pub imgur: String,
+    pub nitter: String,
+    pub invidious: String,
+    pub piped: String,
+}
+
+impl Frontends {
+    fn from_env() -> Self {
+        Self {
+            medium: var_or("MEDIUM_FRONTEND", "scribe.rip"),
+            fandom: var_or("FANDOM_FRONTEND", "breezewiki.com"),
+            quora: var_or("QUORA_FRONTEND", "quetre.iii.st"),
+            genius: var_or("GENIUS_FRONTEND", "dumb.privacydev.net"),
+            stackoverflow: var_or("STACKOVERFLOW_FRONTEND", "overflow.hostux.net"),
+            imgur: var_or("IMGUR_FRONTEND", "rimgo.pussthecat.org"),
+            nitter: var_or("NITTER_FRONTEND", "nitter.privacydev.net"),
+            invidious: var_or("INVIDIOUS_FRONTEND", "yewtu.be"),
+            piped: var_or("PIPED_FRONTEND", "piped.video"),
+        }
+    }
+}
+
+/// How many times per minute the bot can be used, 0 meaning without limit.
+#[derive(Clone, Copy)]
+pub struct RateLimits {
+    pub user: u32,
+    pub channel: u32,
+    pub guild: u32,
+}
+
+impl RateLimits {
+    fn from_env() -> Self {
+        Self {
+            user: parsed_var("RATE_LIMIT_USER").unwrap_or(DEFAULT_USER_RATE_LIMIT),
+            channel: parsed_var("RATE_LIMIT_CHANNEL").unwrap_or(DEFAULT_CHANNEL_RATE_LIMIT),
+            guild: parsed_var("RATE_LIMIT_GUILD").unwrap_or(DEFAULT_GUILD_RATE_LIMIT),
+        }
+    }
+}
+
+pub struct Config {
+    /// Instances whose links are recognised with any known path shape,
+    /// not only the distinctive `/@user/<id>` one.
+    pub fediverse_instances: Vec<String>,
+    /// Base URL of the Odesli (song.link) compatible API used to convert music links.
+    pub odesli_api_url: String,
+    pub frontends: Frontends,
+    /// Domains whose links are treated as news articles.
+    pub news_domains: Vec<String>,
+    /// Whether to look up existing Wayback Machine snapshots of news articles.
+    pub wayback_lookup: bool,
+    /// Base URL of the Wayback Machine availability API.
+    pub wayback_api_url: String,
+    /// Path to a file with one malicious domain per line.
+    pub blocklist_path: Option<String>,
+    /// Maximum number of alternative links in a single response.
+    pub max_links_per_response: usize,
+    /// Maximum number of links converted each time the bot is used, the others are left out.
+    pub max_links_per_invocation: usize,
+    pub rate_limits: RateLimits,
+    /// Reacting to a message with this emoji makes the bot reply with alternative links.
+    pub reaction_emoji: Option<String>,
+    /// Whether the bot reads every message sent where it was added, for auto mode and alerts.
+    pub read_messages: bool,
+    /// Path to the file where the settings changed through admin commands are saved.
+    pub settings_path: String,
+}
+
+impl Config {
+    fn from_env() -> Self {
+        Self {
+            fediverse_instances: list_var("FEDIVERSE_INSTANCES")
+                .unwrap_or_else(|| to_owned_list(DEFAULT_FEDIVERSE_INSTANCES)),
+            odesli_api_url: var_or("ODESLI_API_URL", DEFAULT_ODESLI_API_URL),
+            frontends: Frontends::from_env(),
+            news_domains: list_var("NEWS_DOMAINS")
+                .unwrap_or_else(|| to_owned_list(DEFAULT_NEWS_DOMAINS)),
+            wayback_lookup: bool_var("WAYBACK_LOOKUP"),
+            wayback_api_url: var_or("WAYBACK_API_URL", DEFAULT_WAYBACK_API_URL),
+            blocklist_path: env::var("BLOCKLIST_PATH").ok(),
+            max_links_per_response: parsed_var("MAX_LINKS_PER_RESPONSE")
+                .unwrap_or(DEFAULT_MAX_LINKS_PER_RESPONSE),
+            max_links_per_invocation: parsed_var("MAX_LINKS_PER_INVOCATION")
+                .unwrap_or(DEFAULT_MAX_LINKS_PER_INVOCATION),
+            rate_limits: RateLimits::from_env(),
+            reaction_emoji: env::var("REACTION_EMOJI")
+                .ok()
+                .filter(|emoji| !emoji.is_empty()),
+            read_messages: bool_var("READ_MESSAGES"),
+            settings_path: var_or("SETTINGS_PATH", DEFAULT_SETTINGS_PATH),
+        }
+    }
+}
+
+fn var_or(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+fn parsed_var<T: FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+fn bool_var(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+}
+
+/// Reads a comma-separated list from the given environment variable.
+fn list_var(name: &str) -> Option<Vec<String>> {
+    env::var(name).ok().map(|value| {
+        value
+            .split(',')
+            .map(|item| item.trim().to_lowercase())
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
+
+fn to_owned_list(list: &[&str]) -> Vec<String> {
+    list.iter().map(|item| item.to_string()).collect()
+}
+
+pub fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(Config::from_env)
+}
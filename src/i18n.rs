@@ -0,0 +1,181 @@
+// Translations of the texts shown to users, loaded from the files in `locales/`
+
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    Portuguese,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Portuguese];
+
+    /// The language of a Discord locale, like `pt-BR`, English if there are no translations for it.
+    pub fn from_locale(locale: &str) -> Language {
+        let code = locale.split('-').next().unwrap_or_default();
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+            .unwrap_or_default()
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Portuguese => "pt",
+        }
+    }
+
+    /// The Discord locales that commands are registered with in this language. English ones are
+    /// left out, since English is what Discord shows when there is no translation.
+    fn discord_locales(self) -> &'static [&'static str] {
+        match self {
+            Language::English => &[],
+            Language::Portuguese => &["pt-BR"],
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.json"),
+            Language::Portuguese => include_str!("../locales/pt.json"),
+        }
+    }
+
+    /// The text for the key in this language, or in English if it was not translated yet.
+    pub fn tr(self, key: &str) -> String {
+        self.tr_with(key, &[])
+    }
+
+    /// Like [`Language::tr`], with the `{name}` placeholders replaced by the given values.
+    /// Placeholders in the values themselves are left alone, since they often come from users.
+    pub fn tr_with(self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut rest = translations(self)
+            .get(key)
+            .or_else(|| translations(Language::English).get(key))
+            .map(String::as_str)
+            .unwrap_or(key);
+
+        let mut text = String::new();
+        while let Some((before, after)) = rest.split_once('{') {
+            text.push_str(before);
+            let arg = after.split_once('}').and_then(|(name, after)| {
+                let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+                Some((value, after))
+            });
+            rest = match arg {
+                Some((value, after)) => {
+                    text.push_str(&value.to_string());
+                    after
+                }
+                // not a placeholder, or one without a value
+                None => {
+                    text.push('{');
+                    after
+                }
+            };
+        }
+        text.push_str(rest);
+        text
+    }
+}
+
+fn translations(language: Language) -> &'static HashMap<String, String> {
+    static TRANSLATIONS: OnceLock<Vec<HashMap<String, String>>> = OnceLock::new();
+    let all = TRANSLATIONS.get_or_init(|| {
+        Language::ALL
+            .into_iter()
+            .map(|language| {
+                serde_json::from_str(language.source()).unwrap_or_else(|e| {
+                    panic!("Translations for {} are not valid: {e}", language.code())
+                })
+            })
+            .collect()
+    });
+    &all[language as usize]
+}
+
+/// Things that are shown to users in their own language.
+pub trait Localize {
+    fn localize(&self, language: Language) -> String;
+}
+
+/// The translations of the text for the key, by Discord locale, to register commands with.
+pub fn localizations(key: &str) -> Vec<(&'static str, String)> {
+    Language::ALL
+        .into_iter()
+        .filter(|language| translations(*language).contains_key(key))
+        .flat_map(|language| {
+            language
+                .discord_locales()
+                .iter()
+                .map(move |locale| (*locale, language.tr(key)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{translations, Language};
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_translations_are_complete() {
+        let english = translations(Language::English);
+        for language in Language::ALL {
+            let translated = translations(language);
+            for (key, text) in english {
+                let translation = translated
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{key} is not translated to {language:?}"));
+                assert_eq!(placeholders(text), placeholders(translation), "{key}");
+            }
+            assert_eq!(english.len(), translated.len(), "{language:?}");
+        }
+    }
+
+    #[test]
+    fn test_tr() {
+        assert_eq!(Language::Portuguese, Language::from_locale("pt-BR"));
+        assert_eq!(Language::English, Language::from_locale("ja"));
+        assert_eq!(
+            "...and 3 more links were left out",
+            Language::English.tr_with("response.left_out", &[("count", &3)])
+        );
+        assert_eq!("missing.key", Language::Portuguese.tr("missing.key"));
+    }
+
+    #[test]
+    fn test_tr_with_placeholders_in_values() {
+        assert_eq!(
+            ":x: Could not convert <https://example.com/{error}>: {source}",
+            Language::English.tr_with(
+                "response.failure",
+                &[
+                    ("source", &"<https://example.com/{error}>"),
+                    ("error", &"{source}")
+                ]
+            )
+        );
+        assert_eq!(
+            "a link shown as `paypal.com/{target}` actually leads to `evil.example`",
+            Language::English.tr_with(
+                "warning.masked_link_mismatch",
+                &[
+                    ("text", &"paypal.com/{target}"),
+                    ("target", &"evil.example")
+                ]
+            )
+        );
+    }
+}
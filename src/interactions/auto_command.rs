@@ -0,0 +1,137 @@
+use serenity::{
+    all::{
+        ChannelType, Command, CommandInteraction, CommandOptionType, CommandType, Context,
+        Mentionable, Ready,
+    },
+    async_trait,
+};
+
+use crate::{
+    config::config,
+    settings::{settings, AutoMode},
+};
+
+use super::{
+    add_choice, described_command, described_option, guild_admin_only, installed_guild, language,
+    CommandError, RRCommandInteraction, ResponseBuilder,
+};
+
+const AUTO_COMMAND_NAME: &str = "alturls-auto";
+pub struct AutoCommand;
+
+#[async_trait]
+impl RRCommandInteraction for AutoCommand {
+    fn name(&self) -> String {
+        "auto mode command".to_owned()
+    }
+
+    fn can_handle(&self, interaction: &CommandInteraction) -> bool {
+        interaction.data.name.as_str() == AUTO_COMMAND_NAME
+            && interaction.data.kind == CommandType::ChatInput
+    }
+
+    fn bypasses_policy(&self) -> bool {
+        true
+    }
+
+    async fn handle_impl(
+        &self,
+        interaction: &CommandInteraction,
+    ) -> Result<ResponseBuilder, CommandError> {
+        debug_assert!(self.can_handle(interaction));
+
+        let guild_id = installed_guild(interaction)?;
+        let mut auto_mode = None;
+        let mut alert_channel = None;
+        let mut stop_alerts = false;
+        let mut suppress_embeds = None;
+        for option in &interaction.data.options {
+            match (option.name.as_str(), option.value.as_str()) {
+                ("mode", Some("off")) => auto_mode = Some(AutoMode::Off),
+                ("mode", Some("delete")) => auto_mode = Some(AutoMode::Delete),
+                ("mode", Some("suppress")) => auto_mode = Some(AutoMode::Suppress),
+                ("alerts", _) => alert_channel = option.value.as_channel_id(),
+                ("stop_alerts", _) => stop_alerts = option.value.as_bool().unwrap_or_default(),
+                ("suppress_embeds", _) => suppress_embeds = option.value.as_bool(),
+                _ => {}
+            }
+        }
+
+        settings()
+            .update_guild(guild_id, |guild| {
+                if let Some(auto_mode) = auto_mode {
+                    guild.auto_mode = auto_mode;
+                }
+                if stop_alerts {
+                    guild.alert_channel = None;
+                } else if alert_channel.is_some() {
+                    guild.alert_channel = alert_channel;
+                }
+                if let Some(suppress_embeds) = suppress_embeds {
+                    guild.suppress_embeds = suppress_embeds;
+                }
+            })
+            .await
+            .map_err(|e| {
+                println!("failed to save settings: {e}");
+                CommandError::SaveFailed
+            })?;
+
+        let language = language(interaction);
+        let guild = settings().guild(guild_id);
+        let mode = language.tr(match guild.auto_mode {
+            AutoMode::Off => "auto.mode.off",
+            AutoMode::Delete => "auto.mode.delete",
+            AutoMode::Suppress => "auto.mode.suppress",
+        });
+        let alerts = match guild.alert_channel {
+            Some(channel) => {
+                language.tr_with("auto.alerts.channel", &[("channel", &channel.mention())])
+            }
+            None => language.tr("auto.alerts.none"),
+        };
+        let embeds = language.tr(if guild.suppress_embeds {
+            "auto.embeds.suppressed"
+        } else {
+            "auto.embeds.kept"
+        });
+        let mut response = ResponseBuilder::new();
+        if !config().read_messages {
+            response = response.message(language.tr("auto.not_reading_messages"));
+        }
+        Ok(response
+            .message(mode)
+            .message(alerts)
+            .message(embeds)
+            .private(true))
+    }
+
+    async fn register(&self, ctx: &Context, _ready: &Ready) -> Result<(), serenity::Error> {
+        let mode_option = ["off", "delete", "suppress"].into_iter().fold(
+            described_option(CommandOptionType::String, "mode", "auto_command.mode"),
+            |option, mode| add_choice(option, mode, &format!("auto_command.mode.{mode}")),
+        );
+        let auto_command = guild_admin_only(described_command(
+            AUTO_COMMAND_NAME,
+            "auto_command.description",
+        ))
+        .add_option(mode_option)
+        .add_option(
+            described_option(CommandOptionType::Channel, "alerts", "auto_command.alerts")
+                .channel_types(vec![ChannelType::Text]),
+        )
+        .add_option(described_option(
+            CommandOptionType::Boolean,
+            "stop_alerts",
+            "auto_command.stop_alerts",
+        ))
+        .add_option(described_option(
+            CommandOptionType::Boolean,
+            "suppress_embeds",
+            "auto_command.suppress_embeds",
+        ));
+        Command::create_global_command(&ctx.http, auto_command).await?;
+
+        Ok(())
+    }
+}
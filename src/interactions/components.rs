@@ -0,0 +1,218 @@
+// Components attached to responses: a button to delete them, and menus to swap the shown
+// alternative links for others
+
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, OnceLock},
+};
+
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, ComponentInteractionDataKind, Context, CreateActionRow,
+    CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption, MessageId, UserId,
+};
+use url::Url;
+
+use crate::{
+    i18n::Language,
+    replies::{delete_replies, replies},
+};
+
+use super::ResponseBuilder;
+
+const DELETE_ID: &str = "alturls-delete";
+const FRONTEND_ID: &str = "alturls-frontend";
+
+/// Discord allows this many rows of components in a message, and options in a select menu.
+const MAX_ROWS: usize = 5;
+const MAX_OPTIONS: usize = 25;
+
+/// Discord rejects longer labels, descriptions and placeholders.
+const MAX_LABEL_LENGTH: usize = 100;
+
+/// Only the latest responses are remembered, older ones can no longer have their links swapped.
+const MAX_TRACKED_RESPONSES: usize = 1000;
+
+struct SentResponse {
+    /// The message with the components
+    message: MessageId,
+    response: ResponseBuilder,
+    invoker: UserId,
+}
+
+fn sent_responses() -> &'static Mutex<VecDeque<SentResponse>> {
+    static SENT_RESPONSES: OnceLock<Mutex<VecDeque<SentResponse>>> = OnceLock::new();
+    SENT_RESPONSES.get_or_init(Mutex::default)
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_LABEL_LENGTH).collect()
+}
+
+/// The components of the last page of a response. Alternatives can only be swapped when the
+/// response fits in one page, and only public responses can be deleted.
+pub fn components(
+    response: &ResponseBuilder,
+    single_page: bool,
+    invoker: UserId,
+) -> Vec<CreateActionRow> {
+    let language = response.language();
+    let mut rows = vec![];
+    if single_page {
+        rows.extend(
+            response
+                .alternatives()
+                .enumerate()
+                .filter(|(_, (_, links))| links.len() > 1)
+                .map(|(i, (source, links))| {
+                    let options = links
+                        .iter()
+                        .take(MAX_OPTIONS)
+                        .enumerate()
+                        .map(|(j, link)| {
+                            let host = Url::parse(link.url())
+                                .ok()
+                                .and_then(|url| url.host_str().map(str::to_owned))
+                                .unwrap_or_default();
+                            let option =
+                                CreateSelectMenuOption::new(truncate(&host), j.to_string());
+                            // menus cannot have spoilers, so spoilered links are left out
+                            match source {
+                                Some(_) => option.description(truncate(link.url())),
+                                None => option,
+                            }
+                        })
+                        .collect();
+                    let placeholder = match source {
+                        Some(source) => {
+                            language.tr_with("components.other_frontends", &[("source", &source)])
+                        }
+                        None => language.tr_with(
+                            "components.other_frontends_spoilered",
+                            &[("index", &(i + 1))],
+                        ),
+                    };
+                    CreateActionRow::SelectMenu(
+                        CreateSelectMenu::new(
+                            format!("{FRONTEND_ID}:{i}"),
+                            CreateSelectMenuKind::String { options },
+                        )
+                        .placeholder(truncate(&placeholder)),
+                    )
+                })
+                .take(MAX_ROWS - 1),
+        );
+    }
+    if !response.is_private() {
+        rows.push(CreateActionRow::Buttons(vec![CreateButton::new(format!(
+            "{DELETE_ID}:{invoker}"
+        ))
+        .label(language.tr("components.delete"))
+        .style(ButtonStyle::Danger)]));
+    }
+    rows
+}
+
+/// Remembers a sent response, so that its alternatives can be swapped.
+pub fn track_response(message: MessageId, response: ResponseBuilder, invoker: UserId) {
+    let mut sent = sent_responses().lock().unwrap();
+    if sent.len() == MAX_TRACKED_RESPONSES {
+        sent.pop_front();
+    }
+    sent.push_back(SentResponse {
+        message,
+        response,
+        invoker,
+    });
+}
+
+/// Whether the user invoked the response, or is a moderator who can delete it anyway.
+fn may_change(interaction: &ComponentInteraction, invoker: UserId) -> bool {
+    interaction.user.id == invoker
+        || interaction
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.manage_messages())
+}
+
+/// Responds with the text for the key, only to whoever used the component, in their language.
+async fn respond_privately(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    key: &str,
+) -> serenity::Result<()> {
+    let content = Language::from_locale(&interaction.locale).tr(key);
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .ephemeral(true),
+    );
+    interaction.create_response(&ctx.http, response).await
+}
+
+pub async fn handle_component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+) -> serenity::Result<()> {
+    let Some((id, arg)) = interaction.data.custom_id.split_once(':') else {
+        return Ok(());
+    };
+
+    match (id, &interaction.data.kind) {
+        (DELETE_ID, ComponentInteractionDataKind::Button) => {
+            let Ok(invoker) = arg.parse::<UserId>() else {
+                return Ok(());
+            };
+            if !may_change(interaction, invoker) {
+                return respond_privately(ctx, interaction, "components.delete_not_allowed").await;
+            }
+
+            interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+                .await?;
+            interaction.delete_response(&ctx.http).await?;
+            // the other pages are deleted along with it, and the original embeds restored
+            if let Some(mut reply) = replies().take(interaction.message.id, |_| true) {
+                reply.replies.retain(|&id| id != interaction.message.id);
+                delete_replies(ctx, reply).await?;
+            }
+            Ok(())
+        }
+        (FRONTEND_ID, ComponentInteractionDataKind::StringSelect { values }) => {
+            let (Ok(group), Some(Ok(link))) = (
+                arg.parse::<usize>(),
+                values.first().map(|value| value.parse::<usize>()),
+            ) else {
+                return Ok(());
+            };
+
+            let updated = {
+                let mut sent = sent_responses().lock().unwrap();
+                sent.iter_mut()
+                    .find(|sent| sent.message == interaction.message.id)
+                    .map(|sent| {
+                        let allowed = may_change(interaction, sent.invoker);
+                        if allowed {
+                            sent.response.choose(group, link);
+                        }
+                        (allowed, sent.response.clone(), sent.invoker)
+                    })
+            };
+            let Some((allowed, response, invoker)) = updated else {
+                return respond_privately(ctx, interaction, "components.too_old").await;
+            };
+            if !allowed {
+                return respond_privately(ctx, interaction, "components.change_not_allowed").await;
+            }
+
+            let update = CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(response.pages().concat())
+                    .components(components(&response, true, invoker)),
+            );
+            interaction.create_response(&ctx.http, update).await
+        }
+        _ => Ok(()),
+    }
+}
@@ -1,41 +1,284 @@
+use std::fmt::Display;
+
 use serenity::{
     all::{
-        CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
-        Ready,
+        AuthorizingIntegrationOwner, CommandInteraction, CommandOptionType, CommandType, Context,
+        CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+        EditInteractionResponse, GuildId, InstallationContext, InteractionContext, Message,
+        Permissions, Ready,
     },
     async_trait,
 };
 
+use crate::{
+    i18n::{localizations, Language, Localize},
+    replies::{replies, suppress_embeds, Reply},
+};
+
+mod components;
+pub(crate) use components::{components, handle_component, track_response};
+
+mod auto_command;
+pub(crate) use auto_command::AutoCommand;
+
 mod msg_command;
-pub(crate) use msg_command::MsgCommand;
+pub(crate) use msg_command::{MessageLinks, MsgCommand};
+
+mod platforms_command;
+pub(crate) use platforms_command::PlatformsCommand;
+
+mod policy_command;
+pub(crate) use policy_command::PolicyCommand;
+
+mod prefs_command;
+pub(crate) use prefs_command::PrefsCommand;
+
+mod response;
+pub(crate) use response::ResponseBuilder;
 
 mod slash_command;
 pub(crate) use slash_command::SlashCommand;
 
+/// Lets the command be installed to servers and to users, and be used anywhere.
+fn installable_anywhere(command: CreateCommand) -> CreateCommand {
+    command
+        .integration_types(vec![InstallationContext::Guild, InstallationContext::User])
+        .contexts(vec![
+            InteractionContext::Guild,
+            InteractionContext::BotDm,
+            InteractionContext::PrivateChannel,
+        ])
+}
+
+/// Lets the command only be used in servers that added the bot, by members who can manage them.
+fn guild_admin_only(command: CreateCommand) -> CreateCommand {
+    command
+        .integration_types(vec![InstallationContext::Guild])
+        .contexts(vec![InteractionContext::Guild])
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+}
+
+/// A chat input command, described in every language.
+fn described_command(name: &str, key: &str) -> CreateCommand {
+    localizations(key).into_iter().fold(
+        CreateCommand::new(name)
+            .kind(CommandType::ChatInput)
+            .description(Language::English.tr(key)),
+        |command, (locale, description)| command.description_localized(locale, description),
+    )
+}
+
+/// An option of a command, described in every language.
+fn described_option(kind: CommandOptionType, name: &str, key: &str) -> CreateCommandOption {
+    localizations(key).into_iter().fold(
+        CreateCommandOption::new(kind, name, Language::English.tr(key)),
+        |option, (locale, description)| option.description_localized(locale, description),
+    )
+}
+
+/// Adds a choice to the option, named in every language.
+fn add_choice(option: CreateCommandOption, value: &str, key: &str) -> CreateCommandOption {
+    option.add_string_choice_localized(Language::English.tr(key), value, localizations(key))
+}
+
+/// The guild the command was used in, if the bot was added to it.
+fn installed_guild(interaction: &CommandInteraction) -> Result<GuildId, CommandError> {
+    interaction
+        .guild_id
+        .filter(|_| installed_here(interaction))
+        .ok_or(CommandError::NotInGuild)
+}
+
+/// Whether the bot is where the interaction happened, and not only installed by the user.
+/// Otherwise the bot cannot read the channel or manage its webhooks, and its responses
+/// default to being private, since the others there did not add it.
+pub fn installed_here(interaction: &CommandInteraction) -> bool {
+    match interaction.context {
+        Some(InteractionContext::Guild) => interaction
+            .authorizing_integration_owners
+            .0
+            .iter()
+            .any(|owner| matches!(owner, AuthorizingIntegrationOwner::GuildInstall(Some(_)))),
+        Some(InteractionContext::BotDm) | None => true,
+        Some(_) => false,
+    }
+}
+
+/// Why a command could not respond with any links.
+#[derive(Debug)]
+pub enum CommandError {
+    NoLinks,
+    NoSupportedLinks,
+    NotInGuild,
+    SaveFailed,
+    TooManyFrontends,
+    UnknownFrontend(String),
+}
+
+impl Localize for CommandError {
+    fn localize(&self, language: Language) -> String {
+        match self {
+            CommandError::NoLinks => language.tr("error.no_links"),
+            CommandError::NoSupportedLinks => language.tr("error.no_supported_links"),
+            CommandError::NotInGuild => language.tr("error.not_in_guild"),
+            CommandError::SaveFailed => language.tr("error.save_failed"),
+            CommandError::TooManyFrontends => language.tr_with(
+                "error.too_many_frontends",
+                &[("max", &prefs_command::MAX_FRONTENDS)],
+            ),
+            CommandError::UnknownFrontend(frontend) => {
+                language.tr_with("error.unknown_frontend", &[("frontend", frontend)])
+            }
+        }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+/// Responds with a message that only whoever used the command sees.
+pub async fn respond_privately(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    content: impl Into<String>,
+) -> Result<(), serenity::Error> {
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .ephemeral(true),
+    );
+    interaction.create_response(&ctx.http, response).await
+}
+
+/// The language of whoever used the command.
+pub fn language(interaction: &CommandInteraction) -> Language {
+    Language::from_locale(&interaction.locale)
+}
+
+/// Tells whoever used the command what went wrong, in their language.
+pub async fn respond_error(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    error: impl Localize,
+) -> Result<(), serenity::Error> {
+    let language = language(interaction);
+    let error = error.localize(language);
+    let content = language.tr_with("error", &[("error", &error)]);
+    respond_privately(ctx, interaction, content).await
+}
+
 #[async_trait]
 pub trait RRCommandInteraction {
     fn name(&self) -> String;
 
     fn can_handle(&self, interaction: &CommandInteraction) -> bool;
 
+    /// Whether the command is used regardless of the guild's policy, like admin commands, which
+    /// could otherwise not undo a policy that is too strict.
+    fn bypasses_policy(&self) -> bool {
+        false
+    }
+
+    /// Whether using the command counts towards the rate limits. Commands that bypass the policy
+    /// are not limited either, unless they say otherwise.
+    fn is_rate_limited(&self) -> bool {
+        !self.bypasses_policy()
+    }
+
+    /// The message the command was used on, which the response is a reply to.
+    fn source_message<'a>(&self, _interaction: &'a CommandInteraction) -> Option<&'a Message> {
+        None
+    }
+
+    /// Whether the response is only shown to whoever used the command. It is known before
+    /// handling the command, since the response is deferred while links are resolved.
+    fn is_private(&self, _interaction: &CommandInteraction) -> bool {
+        true
+    }
+
     async fn handle_impl(
         &self,
         interaction: &CommandInteraction,
-    ) -> Result<CreateInteractionResponse, String>;
+    ) -> Result<ResponseBuilder, CommandError>;
     async fn handle(
         &self,
         ctx: &Context,
         interaction: &CommandInteraction,
     ) -> Result<(), serenity::Error> {
+        // Discord only waits 3 seconds for a response, and resolving links can take longer
+        let deferred_private = self.is_private(interaction);
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Defer(
+                    CreateInteractionResponseMessage::new().ephemeral(deferred_private),
+                ),
+            )
+            .await?;
+
+        let language = language(interaction);
         let response = match self.handle_impl(interaction).await {
-            Ok(r) => r,
-            Err(e) => CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new()
-                    .content(format!("Error: {e}"))
-                    .ephemeral(true),
-            ),
-        };
-        interaction.create_response(&ctx.http, response).await?;
+            Ok(response) => response,
+            Err(e) => {
+                let error = e.localize(language);
+                ResponseBuilder::new()
+                    .message(language.tr_with("error", &[("error", &error)]))
+                    .private(true)
+            }
+        }
+        .in_language(language);
+        let private = response.is_private();
+        let pages = response.pages();
+        let components = components(&response, pages.len() <= 1, interaction.user.id);
+
+        // the first page replaces the deferred response, the others are sent as follow-ups,
+        // and the last page gets the components. A public deferred response cannot be made
+        // private, so it is deleted and every page is sent as a follow-up instead.
+        let mut pages = pages.into_iter().peekable();
+        let mut reply_ids = vec![];
+        if private == deferred_private {
+            let mut edit = EditInteractionResponse::new().content(pages.next().unwrap_or_default());
+            if pages.peek().is_none() {
+                edit = edit.components(components.clone());
+            }
+            reply_ids.push(interaction.edit_response(&ctx.http, edit).await?.id);
+        } else {
+            interaction.delete_response(&ctx.http).await?;
+        }
+        while let Some(page) = pages.next() {
+            let mut followup = CreateInteractionResponseFollowup::new()
+                .content(page)
+                .ephemeral(private);
+            if pages.peek().is_none() {
+                followup = followup.components(components.clone());
+            }
+            reply_ids.push(interaction.create_followup(&ctx.http, followup).await?.id);
+        }
+
+        if let Some(&last_page) = reply_ids.last().filter(|_| !components.is_empty()) {
+            track_response(last_page, response.clone(), interaction.user.id);
+        }
+        // the pages of public responses are deleted together, and others only see the embeds
+        // of the source message that the response makes unnecessary
+        if !private {
+            let source = self.source_message(interaction);
+            let suppressed = match source {
+                Some(source) => response.has_embeds() && suppress_embeds(ctx, source).await,
+                None => false,
+            };
+            replies().track(Reply {
+                source: source.map(|source| source.id),
+                channel: interaction.channel_id,
+                interaction_token: Some(interaction.token.clone()),
+                author: source.map_or(interaction.user.id, |source| source.author.id),
+                replies: reply_ids,
+                suppressed,
+            });
+        }
         Ok(())
     }
 
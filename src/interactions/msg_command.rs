@@ -1,19 +1,146 @@
 use serenity::{
     all::{
-        Command, CommandInteraction, CommandType, Context, CreateCommand,
-        CreateInteractionResponse, CreateInteractionResponseMessage, Ready,
+        Command, CommandInteraction, CommandType, Context, CreateCommand, Embed, Message, Ready,
     },
     async_trait,
-    futures::future,
 };
 
-use crate::links::find_platform_links;
+use crate::{
+    i18n::localizations,
+    links::{cap_links, dedupe_links, find_platform_links, join_bounded, FoundLink, Platform},
+    policy::disabled_platforms,
+    safety::{check_message, Warning},
+    settings::settings,
+};
 
-use super::RRCommandInteraction;
+use super::{
+    installable_anywhere, installed_here, CommandError, RRCommandInteraction, ResponseBuilder,
+};
 
 const MSG_COMMAND_NAME: &str = "Alt URLs";
 pub struct MsgCommand;
 
+/// Texts of the embeds that might contain links.
+/// Only rich embeds (sent by bots and webhooks) are fully scanned, since the description
+/// of a link preview is written by the linked website and not by whoever sent the message.
+fn embed_texts(embeds: &[Embed]) -> impl Iterator<Item = &str> {
+    embeds.iter().flat_map(|embed| {
+        let rich = embed.kind.as_deref() == Some("rich");
+        [embed.url.as_deref(), embed.title.as_deref()]
+            .into_iter()
+            .chain(
+                [embed.description.as_deref()]
+                    .into_iter()
+                    .chain(embed.fields.iter().map(|field| Some(field.value.as_str())))
+                    .filter(move |_| rich),
+            )
+            .flatten()
+    })
+}
+
+/// Every text of a message that might contain links: its content and embeds,
+/// the messages it forwards, and the message it replies to.
+fn message_texts(msg: &Message) -> Vec<&str> {
+    let mut texts = vec![msg.content.as_str()];
+    texts.extend(embed_texts(&msg.embeds));
+    texts.extend(
+        msg.attachments
+            .iter()
+            .filter_map(|attachment| attachment.description.as_deref()),
+    );
+    for snapshot in &msg.message_snapshots {
+        texts.push(&snapshot.content);
+        texts.extend(embed_texts(&snapshot.embeds));
+    }
+    if let Some(referenced) = &msg.referenced_message {
+        texts.push(&referenced.content);
+        texts.extend(embed_texts(&referenced.embeds));
+        for snapshot in &referenced.message_snapshots {
+            texts.push(&snapshot.content);
+            texts.extend(embed_texts(&snapshot.embeds));
+        }
+    }
+    texts
+}
+
+/// The links in messages, and warnings about them, before any of them is resolved.
+pub struct MessageLinks {
+    warnings: Vec<Warning>,
+    links: Vec<FoundLink>,
+    /// How many links were left out, because there were too many
+    skipped: usize,
+}
+
+impl MessageLinks {
+    /// Finds the links in the messages, leaving out those to the disabled platforms.
+    pub fn find(messages: &[&Message], disabled: &[Platform]) -> Self {
+        let texts = messages
+            .iter()
+            .flat_map(|msg| message_texts(msg))
+            .collect::<Vec<_>>();
+        let mut warnings = vec![];
+        for warning in texts.iter().flat_map(|text| check_message(text)) {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+
+        // the same link is often in more than one place, e.g. in the content and in its embed,
+        // or written differently, e.g. as a share link and as the link it redirects to
+        let mut links = dedupe_links(
+            texts
+                .iter()
+                .flat_map(|text| find_platform_links(text, disabled)),
+        )
+        .into_iter()
+        .filter(FoundLink::is_on_supported_website)
+        .collect::<Vec<_>>();
+        let skipped = cap_links(&mut links);
+
+        Self {
+            warnings,
+            links,
+            skipped,
+        }
+    }
+
+    /// Whether there is nothing to respond with.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty() && self.links.is_empty()
+    }
+
+    /// The alternative links for the links, and the warnings about them.
+    pub async fn response(self) -> Result<ResponseBuilder, CommandError> {
+        let found_links = join_bounded(self.links.into_iter().map(|found| found.resolve())).await;
+        let results = join_bounded(dedupe_links(found_links).into_iter().map(|found| async {
+            let (url, spoiler) = (found.url.clone(), found.spoiler);
+            (url, spoiler, found.alternative_links().await)
+        }))
+        .await;
+
+        let response = results.into_iter().fold(
+            ResponseBuilder::new()
+                .warnings(&self.warnings)
+                .skipped(self.skipped),
+            |response, (url, spoiler, result)| response.link_result(&url, spoiler, result),
+        );
+        if response.is_empty() {
+            Err(CommandError::NoSupportedLinks)
+        } else {
+            Ok(response)
+        }
+    }
+}
+
+/// The alternative links for the links in the messages, and warnings about them.
+/// Links to the disabled platforms are left out.
+pub async fn messages_response(
+    messages: &[&Message],
+    disabled: &[Platform],
+) -> Result<ResponseBuilder, CommandError> {
+    MessageLinks::find(messages, disabled).response().await
+}
+
 #[async_trait]
 impl RRCommandInteraction for MsgCommand {
     fn name(&self) -> String {
@@ -25,36 +152,142 @@ impl RRCommandInteraction for MsgCommand {
             && interaction.data.kind == CommandType::Message
     }
 
+    fn source_message<'a>(&self, interaction: &'a CommandInteraction) -> Option<&'a Message> {
+        interaction.data.resolved.messages.values().next()
+    }
+
+    fn is_private(&self, interaction: &CommandInteraction) -> bool {
+        !installed_here(interaction)
+    }
+
     async fn handle_impl(
         &self,
         interaction: &CommandInteraction,
-    ) -> Result<CreateInteractionResponse, String> {
+    ) -> Result<ResponseBuilder, CommandError> {
         debug_assert!(self.can_handle(interaction));
 
-        let messages = interaction.data.resolved.messages.values();
-        let alt_urls = future::join_all(
-            messages
-                .flat_map(|msg| find_platform_links(&msg.content))
-                .map(|link| link.alternative_links()),
+        let messages = interaction
+            .data
+            .resolved
+            .messages
+            .values()
+            .collect::<Vec<_>>();
+        messages_response(
+            &messages,
+            &disabled_platforms(interaction.guild_id, interaction.channel_id),
         )
         .await
-        .into_iter()
-        .flatten()
-        .map(|link| link.to_string())
-        .collect::<Vec<_>>();
-
-        if alt_urls.is_empty() {
-            Err("Provided links are not supported :(".to_owned())
-        } else {
-            let reply_msg = CreateInteractionResponseMessage::new().content(alt_urls.join("\n"));
-            Ok(CreateInteractionResponse::Message(reply_msg))
-        }
+        .map(|response| {
+            response
+                .prefer(&settings().user(interaction.user.id).frontends)
+                .private(self.is_private(interaction))
+        })
     }
 
     async fn register(&self, ctx: &Context, _ready: &Ready) -> Result<(), serenity::Error> {
-        let msg_command = CreateCommand::new(MSG_COMMAND_NAME).kind(CommandType::Message);
+        // message commands are shown by name, so it is the name that is translated
+        let msg_command = localizations("msg_command.name").into_iter().fold(
+            installable_anywhere(CreateCommand::new(MSG_COMMAND_NAME).kind(CommandType::Message)),
+            |command, (locale, name)| command.name_localized(locale, name),
+        );
         Command::create_global_command(&ctx.http, msg_command).await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serenity::all::{Embed, Message};
+
+    use super::{message_texts, MessageLinks};
+
+    fn embed(kind: &str, url: &str, description: &str) -> Embed {
+        let mut embed = Embed::default();
+        embed.kind = Some(kind.to_string());
+        embed.url = Some(url.to_string());
+        embed.description = Some(description.to_string());
+        embed
+    }
+
+    fn message(content: &str, embeds: Vec<Embed>) -> Message {
+        let mut msg = Message::default();
+        msg.content = content.to_string();
+        msg.embeds = embeds;
+        msg
+    }
+
+    /// A message forwarding another with the given content and embeds.
+    fn forward(content: &str, embeds: Vec<Embed>) -> Message {
+        let mut msg = message("", vec![]);
+        msg.message_snapshots = vec![serde_json::from_value(json!({
+            "content": content,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "edited_timestamp": null,
+            "mentions": [],
+            "attachments": [],
+            "embeds": embeds,
+            "type": 0,
+            "flags": null,
+        }))
+        .unwrap()];
+        msg
+    }
+
+    #[test]
+    fn test_message_texts() {
+        let mut msg = message(
+            "look https://x.com/a/status/1",
+            vec![
+                // the description of link previews is written by the linked website
+                embed(
+                    "link",
+                    "https://x.com/a/status/1",
+                    "https://x.com/spam/status/2",
+                ),
+                embed(
+                    "rich",
+                    "https://x.com/b/status/3",
+                    "https://x.com/c/status/4",
+                ),
+            ],
+        );
+        msg.referenced_message = Some(Box::new(forward("https://x.com/d/status/5", vec![])));
+
+        assert_eq!(
+            vec![
+                "look https://x.com/a/status/1",
+                "https://x.com/a/status/1",
+                "https://x.com/b/status/3",
+                "https://x.com/c/status/4",
+                "",
+                "https://x.com/d/status/5",
+            ],
+            message_texts(&msg)
+        );
+    }
+
+    #[test]
+    fn test_links_are_deduplicated_across_sources() {
+        let forwarded = forward(
+            "https://twitter.com/a/status/1",
+            vec![embed("link", "https://x.com/a/status/1", "")],
+        );
+        let mut reply = message(
+            "https://x.com/a/status/1 https://youtu.be/AAAAAAAAAAA",
+            vec![],
+        );
+        reply.referenced_message = Some(Box::new(forwarded));
+
+        let links = MessageLinks::find(&[&reply], &[])
+            .links
+            .into_iter()
+            .map(|found| found.url)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["https://x.com/a/status/1", "https://youtu.be/AAAAAAAAAAA"],
+            links
+        );
+    }
+}
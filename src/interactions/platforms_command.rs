@@ -0,0 +1,146 @@
+use serenity::{
+    all::{
+        Command, CommandInteraction, CommandOptionType, CommandType, Context, Mentionable, Ready,
+    },
+    async_trait,
+};
+
+use crate::{
+    i18n::{Language, Localize},
+    links::Platform,
+    settings::settings,
+};
+
+use super::{
+    add_choice, described_command, described_option, guild_admin_only, installed_guild, language,
+    CommandError, RRCommandInteraction, ResponseBuilder,
+};
+
+const PLATFORMS_COMMAND_NAME: &str = "alturls-platforms";
+pub struct PlatformsCommand;
+
+fn names(platforms: impl IntoIterator<Item = Platform>, language: Language) -> String {
+    platforms
+        .into_iter()
+        .map(|platform| platform.localize(language))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[async_trait]
+impl RRCommandInteraction for PlatformsCommand {
+    fn name(&self) -> String {
+        "platforms command".to_owned()
+    }
+
+    fn can_handle(&self, interaction: &CommandInteraction) -> bool {
+        interaction.data.name.as_str() == PLATFORMS_COMMAND_NAME
+            && interaction.data.kind == CommandType::ChatInput
+    }
+
+    fn bypasses_policy(&self) -> bool {
+        true
+    }
+
+    async fn handle_impl(
+        &self,
+        interaction: &CommandInteraction,
+    ) -> Result<ResponseBuilder, CommandError> {
+        debug_assert!(self.can_handle(interaction));
+
+        let guild_id = installed_guild(interaction)?;
+        let mut platform = None;
+        let mut enabled = None;
+        let mut channel = None;
+        for option in &interaction.data.options {
+            match option.name.as_str() {
+                "platform" => platform = option.value.as_str().and_then(Platform::from_id),
+                "enabled" => enabled = option.value.as_bool(),
+                "channel" => channel = option.value.as_channel_id(),
+                _ => {}
+            }
+        }
+
+        if let (Some(platform), Some(enabled)) = (platform, enabled) {
+            settings()
+                .update_guild(guild_id, |guild| {
+                    guild.policy.switch_platform(platform, enabled, channel)
+                })
+                .await
+                .map_err(|e| {
+                    println!("failed to save settings: {e}");
+                    CommandError::SaveFailed
+                })?;
+        }
+
+        let language = language(interaction);
+        let policy = settings().guild(guild_id).policy;
+        let mut response = ResponseBuilder::new().private(true);
+        response = match policy.disabled_platforms.as_slice() {
+            [] => response.message(language.tr("platforms.all_enabled")),
+            disabled => response.message(language.tr_with(
+                "platforms.disabled",
+                &[("platforms", &names(disabled.iter().copied(), language))],
+            )),
+        };
+        for (channel, platforms) in &policy.channel_platforms {
+            let switched = |enabled: bool| {
+                names(
+                    Platform::ALL
+                        .into_iter()
+                        .filter(|platform| platforms.get(platform) == Some(&enabled)),
+                    language,
+                )
+            };
+            let mut line =
+                language.tr_with("platforms.channel", &[("channel", &channel.mention())]);
+            for (enabled, key) in [
+                (true, "platforms.channel.enabled"),
+                (false, "platforms.channel.disabled"),
+            ] {
+                let switched = switched(enabled);
+                if !switched.is_empty() {
+                    line.push(' ');
+                    line.push_str(&language.tr_with(key, &[("platforms", &switched)]));
+                }
+            }
+            response = response.message(line);
+        }
+        Ok(response)
+    }
+
+    async fn register(&self, ctx: &Context, _ready: &Ready) -> Result<(), serenity::Error> {
+        let platform_option = Platform::ALL.into_iter().fold(
+            described_option(
+                CommandOptionType::String,
+                "platform",
+                "platforms_command.platform",
+            ),
+            |option, platform| {
+                add_choice(
+                    option,
+                    platform.id(),
+                    &format!("platform.{}", platform.id()),
+                )
+            },
+        );
+        let platforms_command = guild_admin_only(described_command(
+            PLATFORMS_COMMAND_NAME,
+            "platforms_command.description",
+        ))
+        .add_option(platform_option)
+        .add_option(described_option(
+            CommandOptionType::Boolean,
+            "enabled",
+            "platforms_command.enabled",
+        ))
+        .add_option(described_option(
+            CommandOptionType::Channel,
+            "channel",
+            "platforms_command.channel",
+        ));
+        Command::create_global_command(&ctx.http, platforms_command).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use serenity::{
+    all::{
+        Command, CommandInteraction, CommandOptionType, CommandType, Context, Mentionable, Ready,
+    },
+    async_trait,
+};
+
+use crate::settings::settings;
+
+use super::{
+    add_choice, described_command, described_option, guild_admin_only, installed_guild, language,
+    CommandError, RRCommandInteraction, ResponseBuilder,
+};
+
+const POLICY_COMMAND_NAME: &str = "alturls-policy";
+pub struct PolicyCommand;
+
+/// Adds the item to the list, or removes it, without duplicates.
+fn toggle<T: PartialEq>(list: &mut Vec<T>, item: T, present: bool) {
+    list.retain(|other| *other != item);
+    if present {
+        list.push(item);
+    }
+}
+
+fn mentions(list: &[impl Mentionable]) -> String {
+    list.iter()
+        .map(|item| item.mention().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[async_trait]
+impl RRCommandInteraction for PolicyCommand {
+    fn name(&self) -> String {
+        "policy command".to_owned()
+    }
+
+    fn can_handle(&self, interaction: &CommandInteraction) -> bool {
+        interaction.data.name.as_str() == POLICY_COMMAND_NAME
+            && interaction.data.kind == CommandType::ChatInput
+    }
+
+    fn bypasses_policy(&self) -> bool {
+        true
+    }
+
+    async fn handle_impl(
+        &self,
+        interaction: &CommandInteraction,
+    ) -> Result<ResponseBuilder, CommandError> {
+        debug_assert!(self.can_handle(interaction));
+
+        let guild_id = installed_guild(interaction)?;
+        let mut channel = None;
+        let mut action = None;
+        let mut role = None;
+        let mut exempt = None;
+        for option in &interaction.data.options {
+            match option.name.as_str() {
+                "channel" => channel = option.value.as_channel_id(),
+                "action" => action = option.value.as_str(),
+                "role" => role = option.value.as_role_id(),
+                "exempt" => exempt = option.value.as_bool(),
+                _ => {}
+            }
+        }
+        // the command is used in the channel to change when no other one is given
+        let channel = channel.unwrap_or(interaction.channel_id);
+
+        settings()
+            .update_guild(guild_id, |guild| {
+                let policy = &mut guild.policy;
+                match action {
+                    Some("disable") => toggle(&mut policy.disabled_channels, channel, true),
+                    Some("enable") => toggle(&mut policy.disabled_channels, channel, false),
+                    Some("allow_auto") => toggle(&mut policy.auto_mode_channels, channel, true),
+                    Some("disallow_auto") => toggle(&mut policy.auto_mode_channels, channel, false),
+                    _ => {}
+                }
+                if let (Some(role), Some(exempt)) = (role, exempt) {
+                    toggle(&mut policy.exempt_roles, role, exempt);
+                }
+            })
+            .await
+            .map_err(|e| {
+                println!("failed to save settings: {e}");
+                CommandError::SaveFailed
+            })?;
+
+        let language = language(interaction);
+        let policy = settings().guild(guild_id).policy;
+        let disabled = match policy.disabled_channels.as_slice() {
+            [] => language.tr("policy.channels.all_enabled"),
+            channels => language.tr_with(
+                "policy.channels.disabled",
+                &[("channels", &mentions(channels))],
+            ),
+        };
+        let auto_mode = match policy.auto_mode_channels.as_slice() {
+            [] => language.tr("policy.auto_mode.all"),
+            channels => language.tr_with(
+                "policy.auto_mode.only",
+                &[("channels", &mentions(channels))],
+            ),
+        };
+        let exempt = match policy.exempt_roles.as_slice() {
+            [] => language.tr("policy.roles.none"),
+            roles => language.tr_with("policy.roles.exempt", &[("roles", &mentions(roles))]),
+        };
+        Ok(ResponseBuilder::new()
+            .message(disabled)
+            .message(auto_mode)
+            .message(exempt)
+            .private(true))
+    }
+
+    async fn register(&self, ctx: &Context, _ready: &Ready) -> Result<(), serenity::Error> {
+        let action_option = ["disable", "enable", "allow_auto", "disallow_auto"]
+            .into_iter()
+            .fold(
+                described_option(CommandOptionType::String, "action", "policy_command.action"),
+                |option, action| {
+                    add_choice(option, action, &format!("policy_command.action.{action}"))
+                },
+            );
+        let policy_command = guild_admin_only(described_command(
+            POLICY_COMMAND_NAME,
+            "policy_command.description",
+        ))
+        .add_option(action_option)
+        .add_option(described_option(
+            CommandOptionType::Channel,
+            "channel",
+            "policy_command.channel",
+        ))
+        .add_option(described_option(
+            CommandOptionType::Role,
+            "role",
+            "policy_command.role",
+        ))
+        .add_option(described_option(
+            CommandOptionType::Boolean,
+            "exempt",
+            "policy_command.exempt",
+        ));
+        Command::create_global_command(&ctx.http, policy_command).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use serenity::{
+    all::{Command, CommandInteraction, CommandOptionType, CommandType, Context, Ready},
+    async_trait,
+};
+
+use crate::{links::is_known_frontend, settings::settings};
+
+use super::{
+    described_command, described_option, installable_anywhere, language, CommandError,
+    RRCommandInteraction, ResponseBuilder,
+};
+
+const PREFS_COMMAND_NAME: &str = "alturls-prefs";
+
+/// More preferred frontends than this are not worth storing for each user.
+pub const MAX_FRONTENDS: usize = 10;
+
+/// Discord rejects longer values of the frontends option.
+const MAX_FRONTENDS_LENGTH: u16 = 200;
+pub struct PrefsCommand;
+
+/// Parses comma-separated frontends, where `none` or nothing at all means none.
+fn parse_frontends(frontends: &str) -> Result<Vec<String>, CommandError> {
+    let frontends = frontends
+        .split(',')
+        .map(|frontend| frontend.trim().to_lowercase())
+        .filter(|frontend| !frontend.is_empty() && frontend != "none")
+        .collect::<Vec<_>>();
+    if frontends.len() > MAX_FRONTENDS {
+        return Err(CommandError::TooManyFrontends);
+    }
+    match frontends
+        .iter()
+        .find(|frontend| !is_known_frontend(frontend))
+    {
+        Some(unknown) => Err(CommandError::UnknownFrontend(unknown.clone())),
+        None => Ok(frontends),
+    }
+}
+
+#[async_trait]
+impl RRCommandInteraction for PrefsCommand {
+    fn name(&self) -> String {
+        "preferences command".to_owned()
+    }
+
+    fn can_handle(&self, interaction: &CommandInteraction) -> bool {
+        interaction.data.name.as_str() == PREFS_COMMAND_NAME
+            && interaction.data.kind == CommandType::ChatInput
+    }
+
+    fn bypasses_policy(&self) -> bool {
+        true
+    }
+
+    fn is_rate_limited(&self) -> bool {
+        true
+    }
+
+    async fn handle_impl(
+        &self,
+        interaction: &CommandInteraction,
+    ) -> Result<ResponseBuilder, CommandError> {
+        debug_assert!(self.can_handle(interaction));
+
+        let mut frontends = None;
+        let mut opt_out = None;
+        for option in &interaction.data.options {
+            match option.name.as_str() {
+                "frontends" => {
+                    frontends = option.value.as_str().map(parse_frontends).transpose()?
+                }
+                "opt_out" => opt_out = option.value.as_bool(),
+                _ => {}
+            }
+        }
+
+        if frontends.is_some() || opt_out.is_some() {
+            settings()
+                .update_user(interaction.user.id, |user| {
+                    if let Some(frontends) = frontends {
+                        user.frontends = frontends;
+                    }
+                    if let Some(opt_out) = opt_out {
+                        user.opt_out = opt_out;
+                    }
+                })
+                .await
+                .map_err(|e| {
+                    println!("failed to save settings: {e}");
+                    CommandError::SaveFailed
+                })?;
+        }
+
+        let language = language(interaction);
+        let user = settings().user(interaction.user.id);
+        let frontends = if user.frontends.is_empty() {
+            language.tr("prefs.frontends.all")
+        } else {
+            let frontends = user.frontends.join(", ");
+            language.tr_with("prefs.frontends.preferred", &[("frontends", &frontends)])
+        };
+        let opt_out = language.tr(if user.opt_out {
+            "prefs.opt_out.on"
+        } else {
+            "prefs.opt_out.off"
+        });
+        Ok(ResponseBuilder::new()
+            .message(frontends)
+            .message(opt_out)
+            .private(true))
+    }
+
+    async fn register(&self, ctx: &Context, _ready: &Ready) -> Result<(), serenity::Error> {
+        let prefs_command = installable_anywhere(described_command(
+            PREFS_COMMAND_NAME,
+            "prefs_command.description",
+        ))
+        .add_option(
+            described_option(
+                CommandOptionType::String,
+                "frontends",
+                "prefs_command.frontends",
+            )
+            .max_length(MAX_FRONTENDS_LENGTH),
+        )
+        .add_option(described_option(
+            CommandOptionType::Boolean,
+            "opt_out",
+            "prefs_command.opt_out",
+        ));
+        Command::create_global_command(&ctx.http, prefs_command).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,465 @@
+// Building responses that fit within Discord's message length limit
+
+use std::fmt::Display;
+
+use crate::{
+    config::config,
+    i18n::{Language, Localize},
+    links::{Link, LinkError},
+    safety::Warning,
+};
+
+/// Discord rejects messages with more characters than this.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// A source link, written as it was in the message.
+#[derive(Clone)]
+struct Source {
+    url: String,
+    /// Whether the link was in a spoiler, where it is kept when shown
+    spoiler: bool,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.spoiler {
+            write!(f, "||<{}>||", self.url)
+        } else {
+            write!(f, "<{}>", self.url)
+        }
+    }
+}
+
+/// Alternative links for the same source link.
+#[derive(Clone)]
+struct LinkGroup {
+    source: Source,
+    links: Vec<Link>,
+    /// The only alternative to show, if one was chosen
+    chosen: Option<usize>,
+}
+
+#[derive(Default, Clone)]
+pub struct ResponseBuilder {
+    messages: Vec<String>,
+    warnings: Vec<Warning>,
+    /// Source links that could not be converted, and why
+    failures: Vec<(Source, LinkError)>,
+    groups: Vec<LinkGroup>,
+    /// How many links were not converted at all, because there were too many
+    skipped: usize,
+    has_embeds: bool,
+    private: bool,
+    language: Language,
+}
+
+impl ResponseBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plain messages, like confirmations of admin commands, shown first.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.messages.push(message.into());
+        self
+    }
+
+    /// Safety warnings, which stand out at the top of the response.
+    pub fn warnings(mut self, warnings: &[Warning]) -> Self {
+        self.warnings.extend(warnings.iter().cloned());
+        self
+    }
+
+    /// The alternative links for a link, or why there are none.
+    /// Spoilered links are only shown in spoilers.
+    pub fn link_result(
+        self,
+        url: &str,
+        spoiler: bool,
+        result: Result<Vec<Link>, LinkError>,
+    ) -> Self {
+        let source = Source {
+            url: url.to_owned(),
+            spoiler,
+        };
+        match result {
+            Ok(links) => self.group(source, links),
+            Err(e) => self.failure(source, e),
+        }
+    }
+
+    fn group(mut self, source: Source, links: impl IntoIterator<Item = Link>) -> Self {
+        let links = links
+            .into_iter()
+            .inspect(|link| self.has_embeds |= link.is_embed())
+            .collect::<Vec<_>>();
+        if !links.is_empty() {
+            self.groups.push(LinkGroup {
+                source,
+                links,
+                chosen: None,
+            });
+        }
+        self
+    }
+
+    /// The source links, unless they are spoilered, along with all of their alternatives.
+    pub fn alternatives(&self) -> impl Iterator<Item = (Option<&str>, &[Link])> {
+        self.groups.iter().map(|group| {
+            let source = Some(group.source.url.as_str()).filter(|_| !group.source.spoiler);
+            (source, group.links.as_slice())
+        })
+    }
+
+    /// Only shows the alternatives on the most preferred frontend that has any,
+    /// for the source links that have some.
+    pub fn prefer(mut self, frontends: &[String]) -> Self {
+        for group in &mut self.groups {
+            group.chosen = frontends
+                .iter()
+                .find_map(|frontend| group.links.iter().position(|link| link.is_on(frontend)));
+        }
+        self
+    }
+
+    /// Only shows the chosen alternative of a source link, returning whether there is such.
+    pub fn choose(&mut self, group: usize, link: usize) -> bool {
+        match self.groups.get_mut(group) {
+            Some(group) if link < group.links.len() => {
+                group.chosen = Some(link);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Counts links that were left out before being converted.
+    pub fn skipped(mut self, skipped: usize) -> Self {
+        self.skipped += skipped;
+        self
+    }
+
+    fn failure(mut self, source: Source, error: LinkError) -> Self {
+        self.failures.push((source, error));
+        self
+    }
+
+    /// Only show the response to whoever used the command.
+    pub fn private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    /// Writes the response in the given language, English by default.
+    pub fn in_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Responses are private when asked to, and when they only say what went wrong.
+    pub fn is_private(&self) -> bool {
+        self.private
+            || (self.messages.is_empty() && self.warnings.is_empty() && self.groups.is_empty())
+    }
+
+    /// Whether any of the links is embedded, which makes the embeds of the source unnecessary.
+    pub fn has_embeds(&self) -> bool {
+        self.has_embeds
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+            && self.warnings.is_empty()
+            && self.failures.is_empty()
+            && self.groups.is_empty()
+    }
+
+    /// Splits the response into messages that Discord accepts,
+    /// leaving out the links beyond the configured limit.
+    pub fn pages(&self) -> Vec<String> {
+        split_into_pages(
+            self.lines(config().max_links_per_response),
+            MAX_MESSAGE_LENGTH,
+        )
+    }
+
+    /// The lines of the response. Links are grouped under their source link,
+    /// unless there is only one source link.
+    fn lines(&self, max_links: usize) -> Vec<String> {
+        let show_sources = self.groups.len() > 1;
+        let mut remaining = max_links;
+        let mut left_out = self.skipped;
+
+        let language = self.language;
+        let mut lines = self.messages.clone();
+        lines.extend(self.warnings.iter().map(|warning| {
+            let warning = warning.localize(language);
+            language.tr_with("response.warning", &[("warning", &warning)])
+        }));
+        lines.extend(self.failures.iter().map(|(source, error)| {
+            let error = error.localize(language);
+            language.tr_with("response.failure", &[("source", source), ("error", &error)])
+        }));
+        for group in &self.groups {
+            let links = match group.chosen {
+                Some(chosen) => &group.links[chosen..chosen + 1],
+                None => &group.links[..],
+            };
+            let shown = links.len().min(remaining);
+            left_out += links.len() - shown;
+            remaining -= shown;
+            if shown == 0 {
+                continue;
+            }
+
+            if show_sources {
+                lines.push(language.tr_with("response.source", &[("source", &group.source)]));
+            }
+            lines.extend(links.iter().take(shown).map(|link| link.to_string()));
+        }
+        if left_out > 0 {
+            lines.push(language.tr_with("response.left_out", &[("count", &left_out)]));
+        }
+
+        lines
+    }
+}
+
+/// Joins the lines into as few pages as possible, without breaking lines unless they do not fit
+/// in a page by themselves.
+fn split_into_pages(lines: Vec<String>, max_length: usize) -> Vec<String> {
+    let mut pages = vec![];
+    let mut page = String::new();
+
+    for line in lines {
+        let separator = if page.is_empty() { 0 } else { 1 };
+        if page.chars().count() + separator + line.chars().count() <= max_length {
+            if separator > 0 {
+                page.push('\n');
+            }
+            page.push_str(&line);
+            continue;
+        }
+
+        if !page.is_empty() {
+            pages.push(std::mem::take(&mut page));
+        }
+        let mut chars = line.chars().peekable();
+        while chars.peek().is_some() {
+            page = chars.by_ref().take(max_length).collect();
+            if chars.peek().is_some() {
+                pages.push(std::mem::take(&mut page));
+            }
+        }
+    }
+    if !page.is_empty() {
+        pages.push(page);
+    }
+
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use crate::links::{Link, LinkError, ResolutionError, Unsupported};
+
+    use super::{split_into_pages, ResponseBuilder};
+
+    #[test]
+    fn test_lines() {
+        let mut response = ResponseBuilder::new()
+            .link_result(
+                "https://x.com/a/status/1",
+                false,
+                Ok(vec![
+                    Link::Embed("https://fxtwitter.com/a/status/1".to_string()),
+                    Link::Simple("https://x.com/a/status/1".to_string()),
+                ]),
+            )
+            .link_result(
+                "https://example.com",
+                false,
+                Err(LinkError::Unsupported(Unsupported::Domain)),
+            )
+            .link_result(
+                "https://pixiv.net/artworks/1",
+                false,
+                Ok(vec![
+                    Link::Embed("https://www.phixiv.net/artworks/1".to_string()),
+                    Link::Simple("https://www.pixiv.net/artworks/1".to_string()),
+                ]),
+            );
+
+        assert_eq!(
+            vec![
+                ":x: Could not convert <https://example.com>: website is not supported",
+                "From <https://x.com/a/status/1>:",
+                "https://fxtwitter.com/a/status/1",
+                "<https://x.com/a/status/1>",
+                "From <https://pixiv.net/artworks/1>:",
+                "https://www.phixiv.net/artworks/1",
+                "...and 1 more links were left out",
+            ],
+            response.lines(3)
+        );
+
+        assert!(response.choose(0, 1));
+        assert!(!response.choose(1, 2));
+        assert_eq!(
+            vec![
+                ":x: Could not convert <https://example.com>: website is not supported",
+                "From <https://x.com/a/status/1>:",
+                "<https://x.com/a/status/1>",
+                "From <https://pixiv.net/artworks/1>:",
+                "https://www.phixiv.net/artworks/1",
+                "<https://www.pixiv.net/artworks/1>",
+            ],
+            response.lines(3)
+        );
+
+        // links skipped before being converted are counted with those that did not fit
+        assert_eq!(
+            Some("...and 2 more links were left out"),
+            response.skipped(2).lines(3).last().map(String::as_str)
+        );
+
+        // spoilered source links stay spoilered, like their alternatives
+        let response = ResponseBuilder::new()
+            .link_result(
+                "https://x.com/a/status/1",
+                true,
+                Ok(vec![Link::Spoiler(Box::new(Link::Embed(
+                    "https://fxtwitter.com/a/status/1".to_string(),
+                )))]),
+            )
+            .link_result(
+                "https://x.com/b/status/2",
+                false,
+                Ok(vec![Link::Embed(
+                    "https://fxtwitter.com/b/status/2".to_string(),
+                )]),
+            );
+        assert_eq!(
+            vec![
+                "From ||<https://x.com/a/status/1>||:",
+                "||https://fxtwitter.com/a/status/1||",
+                "From <https://x.com/b/status/2>:",
+                "https://fxtwitter.com/b/status/2",
+            ],
+            response.lines(10)
+        );
+        // the menus to swap alternatives cannot hide spoilered sources
+        assert_eq!(
+            vec![None, Some("https://x.com/b/status/2")],
+            response
+                .alternatives()
+                .map(|(source, _)| source)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_prefer() {
+        let links = || {
+            vec![
+                Link::Simple("https://youtu.be/AAAAAAAAAAA/".to_string()),
+                Link::Simple("https://invidious.example/watch?v=AAAAAAAAAAA".to_string()),
+                Link::Simple("https://piped.example/watch?v=AAAAAAAAAAA".to_string()),
+            ]
+        };
+        let response = ResponseBuilder::new()
+            .link_result(
+                "https://youtube.com/watch?v=AAAAAAAAAAA",
+                false,
+                Ok(links()),
+            )
+            .link_result(
+                "https://youtube.com/watch?v=BBBBBBBBBBB",
+                false,
+                Ok(links()),
+            );
+
+        // the most preferred frontend that has the link wins
+        assert_eq!(
+            vec![
+                "From <https://youtube.com/watch?v=AAAAAAAAAAA>:",
+                "<https://piped.example/watch?v=AAAAAAAAAAA>",
+                "From <https://youtube.com/watch?v=BBBBBBBBBBB>:",
+                "<https://piped.example/watch?v=AAAAAAAAAAA>",
+            ],
+            response
+                .clone()
+                .prefer(&["nitter".to_string(), "piped".to_string()])
+                .lines(10)
+        );
+        // without any preferred frontend, every alternative is shown
+        assert_eq!(
+            response.lines(10),
+            response.clone().prefer(&["nitter".to_string()]).lines(10)
+        );
+        assert_eq!(response.lines(10), response.clone().prefer(&[]).lines(10));
+    }
+
+    #[test]
+    fn test_failure_lines() {
+        let response = ResponseBuilder::new()
+            .link_result(
+                "https://open.spotify.com/track/AAAA",
+                false,
+                Err(LinkError::Resolution(ResolutionError::Timeout)),
+            )
+            .link_result(
+                "https://x.com/a/status/1",
+                false,
+                Ok(vec![Link::Embed(
+                    "https://fxtwitter.com/a/status/1".to_string(),
+                )]),
+            )
+            .link_result(
+                "https://reddit.com/r/rust/s/AAAA",
+                false,
+                Err(LinkError::Resolution(ResolutionError::RateLimited)),
+            )
+            .link_result(
+                "https://amzn.to/3AAAAAA",
+                true,
+                Err(LinkError::Resolution(ResolutionError::UpstreamStatus(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                ))),
+            );
+
+        // failures come first, spoilered if their link was, and a single converted link is not
+        // grouped under its source
+        assert_eq!(
+            vec![
+                ":x: Could not convert <https://open.spotify.com/track/AAAA>: \
+                the website took too long to respond",
+                ":x: Could not convert <https://reddit.com/r/rust/s/AAAA>: \
+                the website is rate limiting us, try again later",
+                ":x: Could not convert ||<https://amzn.to/3AAAAAA>||: \
+                the website responded with 503 Service Unavailable",
+                "https://fxtwitter.com/a/status/1",
+            ],
+            response.lines(10)
+        );
+        assert!(!response.is_private());
+    }
+
+    #[test]
+    fn test_split_into_pages() {
+        let lines = ["aaaa", "bbb", "cc", "dddddddddddd", "e"]
+            .map(|line| line.to_string())
+            .to_vec();
+
+        assert_eq!(
+            vec!["aaaa\nbbb", "cc", "dddddddd", "dddd\ne"],
+            split_into_pages(lines, 8)
+        );
+    }
+}
@@ -1,19 +1,41 @@
 use serenity::{
     all::{
-        Command, CommandInteraction, CommandOptionType, CommandType, Context, CreateCommand,
-        CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage, Ready,
+        Command, CommandDataOptionValue, CommandInteraction, CommandOptionType, CommandType,
+        Context, Ready,
     },
     async_trait,
 };
-use url::Url;
 
-use crate::links::PlatformLink;
+use crate::{
+    links::{
+        cap_links, dedupe_links, find_typed_platform_links, join_bounded, prefer_frontend,
+        FoundLink,
+    },
+    policy::disabled_platforms,
+    safety::check_message,
+    settings::settings,
+};
 
-use super::RRCommandInteraction;
+use super::{
+    described_command, described_option, installable_anywhere, installed_here, CommandError,
+    RRCommandInteraction, ResponseBuilder,
+};
 
 const SLASH_COMMAND_NAME: &str = "alturls";
 pub struct SlashCommand;
 
+fn option<'a>(
+    interaction: &'a CommandInteraction,
+    name: &str,
+) -> Option<&'a CommandDataOptionValue> {
+    interaction
+        .data
+        .options
+        .iter()
+        .find(|option| option.name == name)
+        .map(|option| &option.value)
+}
+
 #[async_trait]
 impl RRCommandInteraction for SlashCommand {
     fn name(&self) -> String {
@@ -28,55 +50,97 @@ impl RRCommandInteraction for SlashCommand {
         }
 
         // sanity checks
-        if interaction.data.options.len() != 1 {
-            println!("slash command should have 1 option");
-            return false;
-        }
-        if interaction.data.options[0].kind() != CommandOptionType::String {
-            println!("slash command should have 1 option");
+        if option(interaction, "url")
+            .and_then(|url| url.as_str())
+            .is_none()
+        {
+            println!("slash command should have a url option");
             return false;
         }
 
         true
     }
 
+    fn is_private(&self, interaction: &CommandInteraction) -> bool {
+        option(interaction, "private")
+            .and_then(|private| private.as_bool())
+            .unwrap_or(!installed_here(interaction))
+    }
+
     async fn handle_impl(
         &self,
         interaction: &CommandInteraction,
-    ) -> Result<CreateInteractionResponse, String> {
+    ) -> Result<ResponseBuilder, CommandError> {
         debug_assert!(self.can_handle(interaction));
 
-        let url = interaction.data.options[0].value.as_str().unwrap();
-        let url = Url::parse(url).map_err(|e| format!("failed to parse url: {e}"))?;
-        let link = PlatformLink::try_from(url)
-            .map_err(|e| format!("failed to parse plaform link: {e}"))?;
-        let alt_urls = link.alternative_links().await;
-
-        if alt_urls.is_empty() {
-            Err("Provided link is not supported :(".to_owned())
-        } else {
-            let content = alt_urls
-                .into_iter()
-                .map(|link| link.to_string())
-                .collect::<Vec<_>>()
-                .join("\n");
-            let reply_msg = CreateInteractionResponseMessage::new().content(content);
-            Ok(CreateInteractionResponse::Message(reply_msg))
+        let text = option(interaction, "url")
+            .and_then(|url| url.as_str())
+            .unwrap();
+        let frontend = option(interaction, "frontend").and_then(|frontend| frontend.as_str());
+
+        let warnings = check_message(text);
+        let found_links = dedupe_links(find_typed_platform_links(
+            text,
+            &disabled_platforms(interaction.guild_id, interaction.channel_id),
+        ));
+        if found_links.is_empty() && warnings.is_empty() {
+            return Err(CommandError::NoLinks);
         }
+        // without a scheme, file names and domains in prose look like links too
+        let mut found_links = found_links
+            .into_iter()
+            .filter(FoundLink::is_on_supported_website)
+            .collect::<Vec<_>>();
+        if found_links.is_empty() && warnings.is_empty() {
+            return Err(CommandError::NoSupportedLinks);
+        }
+        let skipped = cap_links(&mut found_links);
+        let found_links = join_bounded(found_links.into_iter().map(|found| found.resolve())).await;
+
+        let results = join_bounded(dedupe_links(found_links).into_iter().map(|found| async {
+            let (url, spoiler) = (found.url.clone(), found.spoiler);
+            (url, spoiler, found.alternative_links().await)
+        }))
+        .await;
+
+        let response = results.into_iter().fold(
+            ResponseBuilder::new()
+                .warnings(&warnings)
+                .skipped(skipped)
+                .private(self.is_private(interaction)),
+            |response, (url, spoiler, result)| {
+                let result = match frontend {
+                    Some(frontend) => result.map(|links| prefer_frontend(links, frontend)),
+                    None => result,
+                };
+                response.link_result(&url, spoiler, result)
+            },
+        );
+        // an explicitly asked for frontend overrides the preferences
+        Ok(match frontend {
+            Some(_) => response,
+            None => response.prefer(&settings().user(interaction.user.id).frontends),
+        })
     }
 
     async fn register(&self, ctx: &Context, _ready: &Ready) -> Result<(), serenity::Error> {
-        let slash_command = CreateCommand::new(SLASH_COMMAND_NAME)
-            .kind(CommandType::ChatInput)
-            .description("Get alternative URLs for the provided link")
-            .add_option(
-                CreateCommandOption::new(
-                    CommandOptionType::String,
-                    "url",
-                    "The URL to get alternative links for",
-                )
-                .required(true),
-            );
+        let slash_command = installable_anywhere(described_command(
+            SLASH_COMMAND_NAME,
+            "slash_command.description",
+        ))
+        .add_option(
+            described_option(CommandOptionType::String, "url", "slash_command.url").required(true),
+        )
+        .add_option(described_option(
+            CommandOptionType::String,
+            "frontend",
+            "slash_command.frontend",
+        ))
+        .add_option(described_option(
+            CommandOptionType::Boolean,
+            "private",
+            "slash_command.private",
+        ));
         Command::create_global_command(&ctx.http, slash_command).await?;
 
         Ok(())
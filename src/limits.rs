@@ -0,0 +1,192 @@
+// Rate limits on how often the bot is used, so that nobody can make it flood websites with
+// requests, or channels with responses
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use serenity::all::{ChannelId, GuildId, UserId};
+
+use crate::{
+    config::{config, RateLimits},
+    i18n::{Language, Localize},
+};
+
+/// Buckets that have refilled are forgotten once there are more than this many.
+const MAX_TRACKED_BUCKETS: usize = 10000;
+
+/// What the bot is used by, or in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    User(UserId),
+    Channel(ChannelId),
+    Guild(GuildId),
+}
+
+/// Why the bot may not be used right now, and for how long.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Limited {
+    pub scope: Scope,
+    pub retry_after: Duration,
+}
+
+impl Localize for Limited {
+    fn localize(&self, language: Language) -> String {
+        let key = match self.scope {
+            Scope::User(_) => "limited.user",
+            Scope::Channel(_) => "limited.channel",
+            Scope::Guild(_) => "limited.guild",
+        };
+        let seconds = self.retry_after.as_secs().max(1);
+        language.tr_with(key, &[("seconds", &seconds)])
+    }
+}
+
+impl Display for Limited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+/// Holds up to a minute's worth of uses, and is refilled at the same rate.
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, per_minute: u32, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_minute as f64 / 60.0).min(per_minute as f64);
+        self.updated = now;
+    }
+
+    /// How long until there is a token to take.
+    fn wait(&self, per_minute: u32) -> Duration {
+        Duration::from_secs_f64((1.0 - self.tokens).max(0.0) * 60.0 / per_minute as f64)
+    }
+}
+
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Mutex<HashMap<Scope, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::default(),
+        }
+    }
+
+    fn per_minute(&self, scope: Scope) -> u32 {
+        match scope {
+            Scope::User(_) => self.limits.user,
+            Scope::Channel(_) => self.limits.channel,
+            Scope::Guild(_) => self.limits.guild,
+        }
+    }
+
+    /// Takes a token from the bucket of each scope, or none at all if any of them is empty.
+    pub fn acquire(&self, scopes: &[Scope], now: Instant) -> Result<(), Limited> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_TRACKED_BUCKETS {
+            buckets.retain(|scope, bucket| {
+                let per_minute = self.per_minute(*scope);
+                bucket.refill(per_minute, now);
+                bucket.tokens < per_minute as f64
+            });
+        }
+
+        let scopes = scopes
+            .iter()
+            .copied()
+            .filter(|scope| self.per_minute(*scope) > 0)
+            .collect::<Vec<_>>();
+        for &scope in &scopes {
+            let per_minute = self.per_minute(scope);
+            let bucket = buckets.entry(scope).or_insert(TokenBucket {
+                tokens: per_minute as f64,
+                updated: now,
+            });
+            bucket.refill(per_minute, now);
+            if bucket.tokens < 1.0 {
+                return Err(Limited {
+                    scope,
+                    retry_after: bucket.wait(per_minute),
+                });
+            }
+        }
+        for scope in scopes {
+            if let Some(bucket) = buckets.get_mut(&scope) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn rate_limiter() -> &'static RateLimiter {
+    static RATE_LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    RATE_LIMITER.get_or_init(|| RateLimiter::new(config().rate_limits))
+}
+
+/// Counts a use of the bot by the user, in the channel and guild, if it is not used too often.
+pub fn acquire(
+    user: Option<UserId>,
+    channel: ChannelId,
+    guild: Option<GuildId>,
+) -> Result<(), Limited> {
+    let scopes = [
+        user.map(Scope::User),
+        Some(Scope::Channel(channel)),
+        guild.map(Scope::Guild),
+    ];
+    rate_limiter().acquire(
+        &scopes.into_iter().flatten().collect::<Vec<_>>(),
+        Instant::now(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serenity::all::{ChannelId, UserId};
+
+    use crate::config::RateLimits;
+
+    use super::{Limited, RateLimiter, Scope};
+
+    #[test]
+    fn test_rate_limits() {
+        let limiter = RateLimiter::new(RateLimits {
+            user: 2,
+            channel: 3,
+            guild: 0,
+        });
+        let (alice, bob) = (Scope::User(UserId::new(1)), Scope::User(UserId::new(2)));
+        let channel = Scope::Channel(ChannelId::new(10));
+        let start = Instant::now();
+
+        assert_eq!(Ok(()), limiter.acquire(&[alice, channel], start));
+        assert_eq!(Ok(()), limiter.acquire(&[alice, channel], start));
+        assert_eq!(
+            Err(Limited {
+                scope: alice,
+                retry_after: Duration::from_secs(30),
+            }),
+            limiter.acquire(&[alice, channel], start)
+        );
+        // the limited use did not take from the channel
+        assert_eq!(Ok(()), limiter.acquire(&[bob, channel], start));
+        assert!(limiter.acquire(&[bob, channel], start).is_err());
+
+        let later = start + Duration::from_secs(30);
+        assert_eq!(Ok(()), limiter.acquire(&[alice, channel], later));
+    }
+}
@@ -0,0 +1,153 @@
+// Specific logic for news articles, which get archive and paywall-bypass alternatives
+
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use crate::config::config;
+
+use super::{
+    error::check_status, Link, PlatformLink, ResolutionError, Unsupported, RESOLUTION_TIMEOUT,
+};
+
+/// Tracking query parameters removed from article links, besides any `utm_*` ones.
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "ocid", "smid", "cmpid", "ref", "src"];
+
+pub fn is_news_domain(domain: &str) -> bool {
+    config().news_domains.iter().any(|news_domain| {
+        domain == news_domain
+            || domain
+                .strip_suffix(news_domain.as_str())
+                .is_some_and(|subdomain| subdomain.ends_with('.'))
+    })
+}
+
+pub fn parse_article_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    // the front page is not an article
+    if url.path().trim_matches('/').is_empty() {
+        return Err(Unsupported::Path);
+    }
+
+    let mut clean_url = url.clone();
+    clean_url.set_fragment(None);
+    let query = url
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .collect::<Vec<_>>();
+    if query.is_empty() {
+        clean_url.set_query(None);
+    } else {
+        clean_url.query_pairs_mut().clear().extend_pairs(query);
+    }
+
+    Ok(PlatformLink::Article {
+        url: clean_url.to_string(),
+    })
+}
+
+#[derive(Deserialize)]
+struct WaybackResponse {
+    archived_snapshots: WaybackSnapshots,
+}
+
+#[derive(Deserialize)]
+struct WaybackSnapshots {
+    closest: Option<WaybackSnapshot>,
+}
+
+#[derive(Deserialize)]
+struct WaybackSnapshot {
+    available: bool,
+    url: String,
+}
+
+/// The closest archived snapshot of the article, if there is one.
+async fn find_wayback_snapshot(
+    api_url: &str,
+    article_url: &str,
+) -> Result<Option<String>, ResolutionError> {
+    let api_url = Url::parse_with_params(
+        &format!("{}/wayback/available", api_url.trim_end_matches('/')),
+        &[("url", article_url)],
+    )
+    .map_err(|_| ResolutionError::Failed)?;
+
+    let client = Client::builder().timeout(RESOLUTION_TIMEOUT).build()?;
+    let response: WaybackResponse = check_status(client.get(api_url).send().await?)?
+        .json()
+        .await?;
+
+    Ok(response
+        .archived_snapshots
+        .closest
+        .filter(|snapshot| snapshot.available)
+        .map(|snapshot| snapshot.url.replacen("http://", "https://", 1)))
+}
+
+pub async fn alternative_article_links(url: &str) -> Vec<Link> {
+    let config = config();
+    let wayback_api_url = config
+        .wayback_lookup
+        .then_some(config.wayback_api_url.as_str());
+    article_links(url, wayback_api_url).await
+}
+
+/// The alternatives for the article, looking up its latest snapshot through the given
+/// Wayback Machine API, if any.
+async fn article_links(url: &str, wayback_api_url: Option<&str>) -> Vec<Link> {
+    let snapshot = match wayback_api_url {
+        Some(api_url) => find_wayback_snapshot(api_url, url)
+            .await
+            .unwrap_or_else(|e| {
+                // the other links are still worth showing
+                println!("failed to look up {url} on the Wayback Machine: {e}");
+                None
+            }),
+        None => None,
+    };
+    let wayback_link = snapshot.unwrap_or_else(|| format!("https://web.archive.org/web/*/{url}"));
+
+    vec![
+        Link::Simple(url.to_string()),
+        Link::Simple(format!("https://archive.ph/newest/{url}")),
+        Link::Simple(format!(
+            "https://archive.ph/submit/?url={}",
+            url::form_urlencoded::byte_serialize(url.as_bytes()).collect::<String>()
+        )),
+        Link::Simple(wayback_link),
+        Link::Simple(format!("https://12ft.io/{url}")),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::stub::serve, article_links};
+
+    const ARTICLE: &str = "https://www.nytimes.com/2024/01/01/world/some-article.html";
+
+    async fn wayback_link(api_url: Option<&str>) -> String {
+        article_links(ARTICLE, api_url).await[3].url().to_owned()
+    }
+
+    #[tokio::test]
+    async fn test_wayback_snapshot() {
+        let api_url = serve(
+            "200 OK",
+            r#"{"archived_snapshots": {"closest": {"available": true,
+                "url": "http://web.archive.org/web/20240101000000/https://www.nytimes.com/"}}}"#,
+        );
+        assert_eq!(
+            "https://web.archive.org/web/20240101000000/https://www.nytimes.com/",
+            wayback_link(Some(&api_url)).await
+        );
+
+        let search = format!("https://web.archive.org/web/*/{ARTICLE}");
+        let api_url = serve("200 OK", r#"{"archived_snapshots": {}}"#);
+        assert_eq!(search, wayback_link(Some(&api_url)).await);
+        // failed lookups do not take the other links down with them
+        let api_url = serve("503 Service Unavailable", "");
+        assert_eq!(search, wayback_link(Some(&api_url)).await);
+        assert_eq!(5, article_links(ARTICLE, Some(&api_url)).await.len());
+        assert_eq!(search, wayback_link(None).await);
+    }
+}
@@ -0,0 +1,177 @@
+// Reasons why alternative links could not be produced for a link
+
+use std::fmt::Display;
+
+use reqwest::{Response, StatusCode};
+
+use crate::i18n::{Language, Localize};
+
+/// Why a URL is not a supported platform link.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Unsupported {
+    Scheme,
+    Domain,
+    Path,
+}
+
+impl Localize for Unsupported {
+    fn localize(&self, language: Language) -> String {
+        language.tr(match self {
+            Unsupported::Scheme => "link.unsupported.scheme",
+            Unsupported::Domain => "link.unsupported.domain",
+            Unsupported::Path => "link.unsupported.path",
+        })
+    }
+}
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+/// Why a link that needs requests to be made could not be resolved.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ResolutionError {
+    Timeout,
+    RateLimited,
+    UpstreamStatus(StatusCode),
+    /// The request failed, or the response did not have what we were looking for
+    Failed,
+}
+
+impl Localize for ResolutionError {
+    fn localize(&self, language: Language) -> String {
+        match self {
+            ResolutionError::Timeout => language.tr("link.resolution.timeout"),
+            ResolutionError::RateLimited => language.tr("link.resolution.rate_limited"),
+            ResolutionError::UpstreamStatus(status) => {
+                language.tr_with("link.resolution.upstream_status", &[("status", status)])
+            }
+            ResolutionError::Failed => language.tr("link.resolution.failed"),
+        }
+    }
+}
+
+impl Display for ResolutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+impl From<reqwest::Error> for ResolutionError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ResolutionError::Timeout
+        } else if let Some(status) = e.status() {
+            status_error(status)
+        } else {
+            ResolutionError::Failed
+        }
+    }
+}
+
+fn status_error(status: StatusCode) -> ResolutionError {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        ResolutionError::RateLimited
+    } else {
+        ResolutionError::UpstreamStatus(status)
+    }
+}
+
+/// Fails on error statuses, like `Response::error_for_status`, but telling rate limits apart.
+/// Redirects are not errors, since they are what is being looked for when resolving links.
+pub fn check_status(response: Response) -> Result<Response, ResolutionError> {
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        Err(status_error(status))
+    } else {
+        Ok(response)
+    }
+}
+
+/// Why there are no alternative links for a link.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LinkError {
+    Unsupported(Unsupported),
+    Resolution(ResolutionError),
+}
+
+impl Localize for LinkError {
+    fn localize(&self, language: Language) -> String {
+        match self {
+            LinkError::Unsupported(e) => e.localize(language),
+            LinkError::Resolution(e) => e.localize(language),
+        }
+    }
+}
+
+impl Display for LinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+impl From<Unsupported> for LinkError {
+    fn from(e: Unsupported) -> Self {
+        LinkError::Unsupported(e)
+    }
+}
+
+impl From<ResolutionError> for LinkError {
+    fn from(e: ResolutionError) -> Self {
+        LinkError::Resolution(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, time::Duration};
+
+    use reqwest::{redirect, Client, StatusCode};
+
+    use super::{super::stub, check_status, ResolutionError};
+
+    fn serve(status: &'static str) -> String {
+        stub::serve(status, "")
+    }
+
+    async fn status_of(url: &str) -> Result<StatusCode, ResolutionError> {
+        let client = Client::builder()
+            .redirect(redirect::Policy::none())
+            .timeout(Duration::from_millis(200))
+            .build()?;
+        let response = check_status(client.get(url).send().await?)?;
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn test_check_status() {
+        assert_eq!(Ok(StatusCode::OK), status_of(&serve("200 OK")).await);
+        // redirects are what resolving share links looks for
+        assert_eq!(Ok(StatusCode::FOUND), status_of(&serve("302 Found")).await);
+        assert_eq!(
+            Err(ResolutionError::RateLimited),
+            status_of(&serve("429 Too Many Requests")).await
+        );
+        assert_eq!(
+            Err(ResolutionError::UpstreamStatus(StatusCode::NOT_FOUND)),
+            status_of(&serve("404 Not Found")).await
+        );
+        assert_eq!(
+            Err(ResolutionError::UpstreamStatus(
+                StatusCode::SERVICE_UNAVAILABLE
+            )),
+            status_of(&serve("503 Service Unavailable")).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        // connections are queued by the listener, but never answered
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        assert_eq!(Err(ResolutionError::Timeout), status_of(&url).await);
+    }
+}
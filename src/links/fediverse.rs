@@ -0,0 +1,54 @@
+// Specific logic for Mastodon-compatible instances, which can live on any domain
+
+use url::Url;
+
+use crate::config::config;
+
+use super::{Link, PlatformLink, Unsupported};
+
+pub fn parse_fediverse_link(instance: &str, url: &Url) -> Result<PlatformLink, Unsupported> {
+    let known_instance = config()
+        .fediverse_instances
+        .iter()
+        .any(|known| known == instance);
+    // any domain ends up here, so only known instances are known to be unsupported because of the path
+    let unsupported = if known_instance {
+        Unsupported::Path
+    } else {
+        Unsupported::Domain
+    };
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [None; 5].map(|_: Option<&str>| it.next()))
+        .unwrap_or([None; 5])
+    {
+        // /@<username>/<post_id>
+        [Some(username), Some(post_id), None, ..] if username.starts_with('@') => {
+            Ok(PlatformLink::FediversePost {
+                instance: instance.to_string(),
+                username: username.trim_start_matches('@').to_string(),
+                post_id: post_id.parse().map_err(|_| unsupported)?,
+            })
+        }
+        // /users/<username>/statuses/<post_id>
+        [Some("users"), Some(username), Some("statuses"), Some(post_id), None]
+            if known_instance =>
+        {
+            Ok(PlatformLink::FediversePost {
+                instance: instance.to_string(),
+                username: username.to_string(),
+                post_id: post_id.parse().map_err(|_| unsupported)?,
+            })
+        }
+        _ => Err(unsupported),
+    }
+}
+
+pub fn alternative_fediverse_links(instance: &str, username: &str, post_id: u64) -> Vec<Link> {
+    vec![
+        Link::Simple(format!("https://{instance}/@{username}/{post_id}")),
+        Link::Simple(format!("https://elk.zone/{instance}/@{username}/{post_id}")),
+    ]
+}
@@ -0,0 +1,234 @@
+// Discord-markdown-aware link extraction: links in code are ignored, and links in spoilers,
+// suppressed links (`<url>`) and masked links (`[text](url)`) are marked as such
+
+use std::ops::Range;
+
+use linkify::{LinkFinder, LinkKind};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExtractedLink {
+    pub url: String,
+    /// Byte range of the URL in the message
+    pub range: Range<usize>,
+    pub spoiler: bool,
+    pub suppressed: bool,
+}
+
+fn is_http_url(text: &str) -> bool {
+    (text.starts_with("https://") || text.starts_with("http://"))
+        && !text.contains(char::is_whitespace)
+}
+
+/// Parses a masked link at the start of `text`, returning the range of its target
+/// (relative to `text`) and the length of the whole masked link.
+fn parse_masked_link(text: &str) -> Option<(Range<usize>, usize)> {
+    let text_end = text.find(']')?;
+    if text[1..text_end].contains(['[', '\n']) || !text[text_end..].starts_with("](") {
+        return None;
+    }
+
+    // parentheses in the target are balanced, like in `https://en.wikipedia.org/wiki/Rust_(film)`
+    let target_start = text_end + 2;
+    let mut depth = 0;
+    let target_end = target_start
+        + text[target_start..].find(|c| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            c => c == ')',
+        })?;
+    Some((target_start..target_end, target_end + 1))
+}
+
+/// Extracts the links in a message. With `schemeless`, links in plain text do not need a scheme
+/// (`x.com/user/status/1`), and are given `https://`.
+pub fn extract_links(message: &str, schemeless: bool) -> Vec<ExtractedLink> {
+    let mut links = vec![];
+    let mut spoiler = false;
+
+    let mut finder = LinkFinder::new();
+    finder
+        .kinds(&[LinkKind::Url])
+        .url_must_have_scheme(!schemeless);
+
+    // plain text is only searched for links once we know where it ends
+    let mut plain_start = 0;
+    let flush_plain = |links: &mut Vec<ExtractedLink>, start: usize, end: usize, spoiler: bool| {
+        links.extend(
+            finder
+                .links(&message[start..end])
+                .map(|link| ExtractedLink {
+                    url: if link.as_str().contains("://") {
+                        link.as_str().to_string()
+                    } else {
+                        format!("https://{}", link.as_str())
+                    },
+                    range: start + link.start()..start + link.end(),
+                    spoiler,
+                    suppressed: false,
+                }),
+        )
+    };
+
+    let mut i = 0;
+    while i < message.len() {
+        let rest = &message[i..];
+        let char_len = rest.chars().next().map_or(1, char::len_utf8);
+
+        if let Some(escaped) = rest.strip_prefix('\\') {
+            // escaped character
+            i += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+        } else if let Some(end) = rest.strip_prefix("```").and_then(|code| code.find("```")) {
+            // code block, skipped until its end. Discord shows unclosed ones as they are.
+            flush_plain(&mut links, plain_start, i, spoiler);
+            i += 3 + end + 3;
+            plain_start = i;
+        } else if rest.starts_with('`') {
+            // inline code, delimited by the same number of backticks
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            match rest[ticks..].find(&rest[..ticks]) {
+                Some(end) => {
+                    flush_plain(&mut links, plain_start, i, spoiler);
+                    i += ticks + end + ticks;
+                    plain_start = i;
+                }
+                None => i += ticks,
+            }
+        } else if rest.starts_with("||") {
+            flush_plain(&mut links, plain_start, i, spoiler);
+            spoiler = !spoiler;
+            i += 2;
+            plain_start = i;
+        } else if let Some(end) = rest
+            .strip_prefix('<')
+            .and_then(|inner| inner.find('>'))
+            .filter(|end| is_http_url(&rest[1..end + 1]))
+        {
+            // <url>
+            flush_plain(&mut links, plain_start, i, spoiler);
+            links.push(ExtractedLink {
+                url: rest[1..end + 1].to_string(),
+                range: i + 1..i + end + 1,
+                spoiler,
+                suppressed: true,
+            });
+            i += end + 2;
+            plain_start = i;
+        } else if let Some((target, len)) = rest
+            .starts_with('[')
+            .then(|| parse_masked_link(rest))
+            .flatten()
+        {
+            // [text](url) or [text](<url>)
+            flush_plain(&mut links, plain_start, i, spoiler);
+            let target_text = &rest[target.clone()];
+            let suppressed = target_text.starts_with('<') && target_text.ends_with('>');
+            let target = if suppressed {
+                target.start + 1..target.end - 1
+            } else {
+                target
+            };
+            if is_http_url(&rest[target.clone()]) {
+                links.push(ExtractedLink {
+                    url: rest[target.clone()].to_string(),
+                    range: i + target.start..i + target.end,
+                    spoiler,
+                    suppressed,
+                });
+            }
+            i += len;
+            plain_start = i;
+        } else {
+            i += char_len;
+        }
+    }
+    flush_plain(&mut links, plain_start, message.len(), spoiler);
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_links, ExtractedLink};
+
+    #[test]
+    fn test_extract_links() {
+        let message = "plain https://a.example/1 and `https://b.example/2` and
+```
+https://c.example/3
+```
+||https://d.example/4|| <https://e.example/5> [text](https://f.example/6) \\<https://g.example/7>
+``https://h.example/8 ` still code`` [text](<https://i.example/9>) ||[spoiler](https://j.example/10)||";
+
+        let links = extract_links(message, false);
+
+        let link = |url: &str, spoiler: bool, suppressed: bool| {
+            let start = message.find(url).unwrap();
+            ExtractedLink {
+                url: url.to_string(),
+                range: start..start + url.len(),
+                spoiler,
+                suppressed,
+            }
+        };
+        assert_eq!(
+            vec![
+                link("https://a.example/1", false, false),
+                link("https://d.example/4", true, false),
+                link("https://e.example/5", false, true),
+                link("https://f.example/6", false, false),
+                link("https://g.example/7", false, false),
+                link("https://i.example/9", false, true),
+                link("https://j.example/10", true, false),
+            ],
+            links
+        );
+    }
+
+    #[test]
+    fn test_extract_schemeless_links() {
+        let message = "see x.com/foo/status/1 and https://a.example/2, not `b.example/3`";
+
+        let links = extract_links(message, true)
+            .into_iter()
+            .map(|link| link.url)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec!["https://x.com/foo/status/1", "https://a.example/2"],
+            links
+        );
+        assert!(extract_links("see x.com/foo/status/1", false).is_empty());
+    }
+
+    #[test]
+    fn test_extract_links_edge_cases() {
+        let urls = |message: &str| {
+            extract_links(message, false)
+                .into_iter()
+                .map(|link| link.url)
+                .collect::<Vec<_>>()
+        };
+
+        // unclosed code blocks are not code
+        assert_eq!(
+            vec!["https://a.example/1"],
+            urls("``` not closed https://a.example/1")
+        );
+        assert_eq!(
+            vec![
+                "https://en.wikipedia.org/wiki/Rust_(film)",
+                "https://b.example/(a)(b)"
+            ],
+            urls(
+                "[film](https://en.wikipedia.org/wiki/Rust_(film)) \
+                ([nested](https://b.example/(a)(b)))"
+            )
+        );
+    }
+}
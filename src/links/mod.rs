@@ -1,14 +1,116 @@
-use std::{borrow::Cow, fmt::Display};
+use std::{borrow::Cow, fmt::Display, future::Future, ops::Range, time::Duration};
 
-use linkify::{LinkFinder, LinkKind};
+use serenity::futures::{stream, StreamExt};
 use url::Url;
 
+use crate::config::config;
+
+mod error;
+pub use error::{LinkError, ResolutionError, Unsupported};
+
+mod article;
+use article::{alternative_article_links, is_news_domain, parse_article_link};
+
+mod fediverse;
+use fediverse::{alternative_fediverse_links, parse_fediverse_link};
+
+mod markdown;
+use markdown::extract_links;
+
+mod music;
+use music::{
+    alternative_music_links, parse_apple_music_link, parse_deezer_link, parse_soundcloud_link,
+    parse_spotify_link, parse_tidal_link, parse_youtube_music_link,
+};
+pub use music::{MusicKind, MusicService};
+
+mod pixiv;
+use pixiv::{alternative_pixiv_links, parse_pixiv_link};
+
+mod platform;
+pub use platform::Platform;
+
+mod privacy_frontends;
+use privacy_frontends::{
+    alternative_fandom_links, alternative_genius_links, alternative_imgur_links,
+    alternative_medium_links, alternative_quora_links, alternative_stackoverflow_links,
+    parse_fandom_link, parse_genius_link, parse_imgur_link, parse_medium_link, parse_quora_link,
+    parse_stackoverflow_link,
+};
+
+mod redirects;
+use redirects::unwrap_redirects;
+
 mod reddit;
 use reddit::{alternative_reddit_links, resolve_reddit_share_link};
 
+mod shopping;
+use shopping::{
+    alternative_aliexpress_links, alternative_amazon_links, alternative_ebay_links,
+    parse_aliexpress_link, parse_amazon_link, parse_ebay_link, resolve_amazon_short_link,
+    store_tld, AMAZON_TLDS, EBAY_TLDS,
+};
+
+#[cfg(test)]
+mod stub;
+
+mod tumblr;
+use tumblr::{alternative_tumblr_links, parse_tumblr_link, parse_tumblr_subdomain_link};
+
+mod video;
+use video::{
+    alternative_dailymotion_links, alternative_kick_clip_links, alternative_twitch_clip_links,
+    alternative_twitch_vod_links, alternative_vimeo_links, parse_dailymotion_link,
+    parse_dailymotion_short_link, parse_kick_link, parse_twitch_clip_link, parse_twitch_link,
+    parse_vimeo_link,
+};
+
+/// How long to wait for requests made to resolve links.
+const RESOLUTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many links are resolved at the same time, so that websites are not flooded with requests.
+const MAX_CONCURRENT_RESOLUTIONS: usize = 4;
+
+#[derive(Clone)]
 pub enum Link {
     Simple(String),
     Embed(String),
+    Spoiler(Box<Link>),
+}
+
+impl Link {
+    pub fn url(&self) -> &str {
+        match self {
+            Link::Simple(url) | Link::Embed(url) => url,
+            Link::Spoiler(link) => link.url(),
+        }
+    }
+
+    /// Whether the link is on the given frontend, like `fxtwitter` or `old.reddit.com`.
+    /// Only whole labels of the host match, and not the top-level domain by itself.
+    pub fn is_on(&self, frontend: &str) -> bool {
+        let frontend = frontend.trim().trim_matches('.').to_lowercase();
+        let Some(host) = Url::parse(self.url())
+            .ok()
+            .and_then(|url| url.host_str().map(|host| format!(".{host}.")))
+        else {
+            return false;
+        };
+        let tld_start = host[..host.len() - 1].rfind('.').unwrap_or_default();
+
+        !frontend.is_empty()
+            && host
+                .match_indices(&format!(".{frontend}."))
+                .any(|(i, _)| i < tld_start)
+    }
+
+    pub fn is_embed(&self) -> bool {
+        match self {
+            Link::Simple(_) => false,
+            Link::Embed(_) => true,
+            Link::Spoiler(link) => link.is_embed(),
+        }
+    }
 }
 
 impl Display for Link {
@@ -16,8 +118,74 @@ impl Display for Link {
         match self {
             Link::Simple(url) => write!(f, "<{}>", url),
             Link::Embed(url) => write!(f, "{}", url),
+            Link::Spoiler(link) => write!(f, "||{}||", link),
+        }
+    }
+}
+
+/// A link found in a message, along with how it was written there.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FoundLink {
+    /// The URL as written in the message, with `https://` added if it had no scheme
+    pub url: String,
+    pub link: Result<PlatformLink, LinkError>,
+    /// Byte range of the URL in the message
+    pub range: Range<usize>,
+    pub spoiler: bool,
+    pub suppressed: bool,
+}
+
+impl FoundLink {
+    /// Resolves the link, so that it can be told apart from other links by what it points to.
+    pub async fn resolve(self) -> Self {
+        let link = match self.link {
+            Ok(link) => link.resolve().await.map_err(LinkError::from),
+            Err(e) => Err(e),
+        };
+        Self { link, ..self }
+    }
+
+    /// Whether both links point to the same thing. Links that are not supported are only the
+    /// same when written the same way.
+    pub fn same_as(&self, other: &FoundLink) -> bool {
+        match (&self.link, &other.link) {
+            (Ok(link), Ok(other_link)) => link.same_as(other_link),
+            _ => self.url == other.url,
         }
     }
+
+    /// Whether the link is to a website that is supported at all. Links to other websites are
+    /// most likely not meant to be converted.
+    pub fn is_on_supported_website(&self) -> bool {
+        !matches!(
+            self.link,
+            Err(LinkError::Unsupported(
+                Unsupported::Domain | Unsupported::Scheme
+            ))
+        )
+    }
+
+    /// Alternative links that respect the way the original link was written:
+    /// suppressed links do not get embeds, and spoilered links stay spoilered.
+    pub async fn alternative_links(self) -> Result<Vec<Link>, LinkError> {
+        Ok(self
+            .link?
+            .alternative_links()
+            .await?
+            .into_iter()
+            .map(|link| match link {
+                Link::Embed(url) if self.suppressed => Link::Simple(url),
+                link => link,
+            })
+            .map(|link| {
+                if self.spoiler {
+                    Link::Spoiler(Box::new(link))
+                } else {
+                    link
+                }
+            })
+            .collect())
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -42,11 +210,132 @@ pub enum PlatformLink {
         username: String,
         status_id: u64,
     },
+    PixivArtwork {
+        id: u64,
+        page: Option<u32>,
+    },
+    TumblrPost {
+        blog: String,
+        post_id: u64,
+    },
+    FediversePost {
+        instance: String,
+        username: String,
+        post_id: u64,
+    },
+    TwitchClip {
+        slug: String,
+    },
+    TwitchVod {
+        video_id: u64,
+        timestamp: Option<u32>,
+    },
+    KickClip {
+        channel: String,
+        clip_id: String,
+    },
+    VimeoVideo {
+        video_id: u64,
+        hash: Option<String>,
+    },
+    DailymotionVideo {
+        video_id: String,
+    },
+    Music {
+        service: MusicService,
+        kind: MusicKind,
+        id: String,
+    },
+    MediumArticle {
+        post_id: String,
+    },
+    FandomPage {
+        wiki: String,
+        lang: Option<String>,
+        page: String,
+    },
+    QuoraPage {
+        path: String,
+    },
+    GeniusSong {
+        slug: String,
+    },
+    StackOverflowQuestion {
+        question_id: u64,
+    },
+    ImgurPost {
+        path: String,
+    },
+    Article {
+        url: String,
+    },
+    AmazonProduct {
+        tld: String,
+        asin: String,
+    },
+    AmazonShortLink(String),
+    AliExpressProduct {
+        item_id: u64,
+    },
+    EbayProduct {
+        tld: String,
+        item_id: u64,
+    },
+    /// The target of a redirect wrapper, when it is not a supported link by itself
+    RedirectTarget(String),
 }
 
 impl PlatformLink {
-    pub async fn alternative_links(self) -> Vec<Link> {
+    /// Follows share links to the links they point to, other links are already resolved.
+    pub async fn resolve(self) -> Result<PlatformLink, ResolutionError> {
         match self {
+            PlatformLink::RedditShareLink {
+                subreddit,
+                share_id,
+            } => resolve_reddit_share_link(&subreddit, &share_id).await,
+            PlatformLink::AmazonShortLink(short_link) => {
+                resolve_amazon_short_link(&short_link).await
+            }
+            link => Ok(link),
+        }
+    }
+
+    /// Whether both links point to the same thing, even if written differently.
+    pub fn same_as(&self, other: &PlatformLink) -> bool {
+        match (self, other) {
+            // the username in tweet links is ignored, any username works
+            (
+                PlatformLink::Tweet { status_id, .. },
+                PlatformLink::Tweet {
+                    status_id: other_status_id,
+                    ..
+                },
+            ) => status_id == other_status_id,
+            // post ids are unique across subreddits, and subreddit names are case insensitive
+            (
+                PlatformLink::RedditPost {
+                    post_id,
+                    comment_id,
+                    ..
+                },
+                PlatformLink::RedditPost {
+                    post_id: other_post_id,
+                    comment_id: other_comment_id,
+                    ..
+                },
+            ) => post_id == other_post_id && comment_id == other_comment_id,
+            (
+                PlatformLink::AmazonProduct { asin, .. },
+                PlatformLink::AmazonProduct {
+                    asin: other_asin, ..
+                },
+            ) => asin == other_asin,
+            _ => self == other,
+        }
+    }
+
+    pub async fn alternative_links(self) -> Result<Vec<Link>, ResolutionError> {
+        let links = match self.resolve().await? {
             PlatformLink::InstagramReel(reel_id) => vec![
                 Link::Embed(format!("https://www.ddinstagram.com/reel/{reel_id}/")),
                 Link::Simple(format!("https://www.instagram.com/reel/{reel_id}/")),
@@ -62,27 +351,28 @@ impl PlatformLink {
                 video_id,
                 timestamp,
             } => {
+                let frontends = &config().frontends;
                 if let Some(timestamp) = timestamp {
-                    vec![Link::Simple(format!(
-                        "https://youtu.be/{video_id}/?t={timestamp}"
-                    ))]
-                } else {
-                    vec![Link::Simple(format!("https://youtu.be/{video_id}/"))]
-                }
-            }
-            PlatformLink::RedditShareLink {
-                subreddit,
-                share_id,
-            } => {
-                if let Some(PlatformLink::RedditPost {
-                    subreddit,
-                    post_id,
-                    comment_id,
-                }) = resolve_reddit_share_link(&subreddit, &share_id).await
-                {
-                    alternative_reddit_links(&subreddit, &post_id, comment_id.as_deref())
+                    vec![
+                        Link::Simple(format!("https://youtu.be/{video_id}/?t={timestamp}")),
+                        Link::Simple(format!(
+                            "https://{}/watch?v={video_id}&t={timestamp}",
+                            frontends.invidious
+                        )),
+                        Link::Simple(format!(
+                            "https://{}/watch?v={video_id}&t={timestamp}",
+                            frontends.piped
+                        )),
+                    ]
                 } else {
-                    vec![]
+                    vec![
+                        Link::Simple(format!("https://youtu.be/{video_id}/")),
+                        Link::Simple(format!(
+                            "https://{}/watch?v={video_id}",
+                            frontends.invidious
+                        )),
+                        Link::Simple(format!("https://{}/watch?v={video_id}", frontends.piped)),
+                    ]
                 }
             }
             PlatformLink::RedditPost {
@@ -98,26 +388,195 @@ impl PlatformLink {
                     "https://fxtwitter.com/{username}/status/{status_id}"
                 )),
                 Link::Simple(format!("https://x.com/{username}/status/{status_id}")),
+                Link::Simple(format!(
+                    "https://{}/{username}/status/{status_id}",
+                    config().frontends.nitter
+                )),
             ],
-        }
+            PlatformLink::PixivArtwork { id, page } => alternative_pixiv_links(id, page),
+            PlatformLink::TumblrPost { blog, post_id } => alternative_tumblr_links(&blog, post_id),
+            PlatformLink::FediversePost {
+                instance,
+                username,
+                post_id,
+            } => alternative_fediverse_links(&instance, &username, post_id),
+            PlatformLink::TwitchClip { slug } => alternative_twitch_clip_links(&slug),
+            PlatformLink::TwitchVod {
+                video_id,
+                timestamp,
+            } => alternative_twitch_vod_links(video_id, timestamp),
+            PlatformLink::KickClip { channel, clip_id } => {
+                alternative_kick_clip_links(&channel, &clip_id)
+            }
+            PlatformLink::VimeoVideo { video_id, hash } => {
+                alternative_vimeo_links(video_id, hash.as_deref())
+            }
+            PlatformLink::DailymotionVideo { video_id } => alternative_dailymotion_links(&video_id),
+            PlatformLink::Music { service, kind, id } => {
+                alternative_music_links(service, kind, &id).await
+            }
+            PlatformLink::MediumArticle { post_id } => alternative_medium_links(&post_id),
+            PlatformLink::FandomPage { wiki, lang, page } => {
+                alternative_fandom_links(&wiki, lang.as_deref(), &page)
+            }
+            PlatformLink::QuoraPage { path } => alternative_quora_links(&path),
+            PlatformLink::GeniusSong { slug } => alternative_genius_links(&slug),
+            PlatformLink::StackOverflowQuestion { question_id } => {
+                alternative_stackoverflow_links(question_id)
+            }
+            PlatformLink::ImgurPost { path } => alternative_imgur_links(&path),
+            PlatformLink::Article { url } => alternative_article_links(&url).await,
+            PlatformLink::AmazonProduct { tld, asin } => alternative_amazon_links(&tld, &asin),
+            PlatformLink::AliExpressProduct { item_id } => alternative_aliexpress_links(item_id),
+            PlatformLink::EbayProduct { tld, item_id } => alternative_ebay_links(&tld, item_id),
+            PlatformLink::RedirectTarget(url) => vec![Link::Simple(url)],
+            // resolving never gives back another share link
+            PlatformLink::RedditShareLink { .. } | PlatformLink::AmazonShortLink(_) => {
+                return Err(ResolutionError::Failed)
+            }
+        };
+        Ok(links)
     }
 }
 
-pub fn find_platform_links(message: &str) -> Vec<PlatformLink> {
-    LinkFinder::new()
-        .kinds(&[LinkKind::Url])
-        .links(message)
-        .map(|link| link.as_str())
-        .filter_map(|link| Url::parse(link).ok())
-        .map(PlatformLink::try_from)
-        .filter_map(|maybe_link| maybe_link.ok())
+/// Finds the links in the message, leaving out those to the disabled platforms.
+pub fn find_platform_links(message: &str, disabled: &[Platform]) -> Vec<FoundLink> {
+    find_links(message, false, disabled)
+}
+
+/// Like [`find_platform_links`], but also finds links typed without a scheme,
+/// like `x.com/user/status/1`.
+pub fn find_typed_platform_links(text: &str, disabled: &[Platform]) -> Vec<FoundLink> {
+    find_links(text, true, disabled)
+}
+
+fn find_links(message: &str, schemeless: bool, disabled: &[Platform]) -> Vec<FoundLink> {
+    extract_links(message, schemeless)
+        .into_iter()
+        .filter_map(|extracted| {
+            let url = Url::parse(&extracted.url).ok()?;
+            let link = parse_platform_link(url.clone()).map_err(LinkError::from);
+            // left out before anything is resolved, so that no requests are made for them, and
+            // along with the pages of the platform that are not supported
+            let platform = match &link {
+                Ok(link) => Some(link.platform()),
+                Err(_) => url.domain().and_then(Platform::from_domain),
+            };
+            if platform.is_some_and(|platform| disabled.contains(&platform)) {
+                return None;
+            }
+            Some(FoundLink {
+                link,
+                url: extracted.url,
+                range: extracted.range,
+                spoiler: extracted.spoiler,
+                suppressed: extracted.suppressed,
+            })
+        })
         .collect()
 }
 
-pub struct Unsupported;
-impl Display for Unsupported {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Unsupported link")
+/// Leaves out the links beyond the configured limit, returning how many were left out.
+pub fn cap_links(links: &mut Vec<FoundLink>) -> usize {
+    let max = config().max_links_per_invocation;
+    let skipped = links.len().saturating_sub(max);
+    links.truncate(max);
+    skipped
+}
+
+/// Runs the futures resolving links a few at a time, giving back their outputs in order.
+pub async fn join_bounded<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    stream::iter(futures)
+        .buffered(MAX_CONCURRENT_RESOLUTIONS)
+        .collect()
+        .await
+}
+
+/// Keeps the first of the links that point to the same thing.
+pub fn dedupe_links(links: impl IntoIterator<Item = FoundLink>) -> Vec<FoundLink> {
+    let mut deduped: Vec<FoundLink> = vec![];
+    for found in links {
+        if !deduped.iter().any(|other| other.same_as(&found)) {
+            deduped.push(found);
+        }
+    }
+    deduped
+}
+
+/// Keeps the links on the given frontend (e.g. `fxtwitter`), or all of them if none is on it.
+pub fn prefer_frontend(links: Vec<Link>, frontend: &str) -> Vec<Link> {
+    let on_frontend = |link: &Link| link.is_on(frontend);
+    if links.iter().any(on_frontend) {
+        links.into_iter().filter(on_frontend).collect()
+    } else {
+        links
+    }
+}
+
+/// Hosts that alternative links are on, besides the configured frontends.
+const ALTERNATIVE_HOSTS: &[&str] = &[
+    "ddinstagram.com",
+    "instagram.com",
+    "youtu.be",
+    "old.reddit.com",
+    "fxtwitter.com",
+    "x.com",
+    "phixiv.net",
+    "pixiv.net",
+    "tpmblr.com",
+    "tumblr.com",
+    "elk.zone",
+    "clips.twitch.tv",
+    "twitch.tv",
+    "kick.com",
+    "vimeo.com",
+    "dailymotion.com",
+    "dai.ly",
+    "song.link",
+    "open.spotify.com",
+    "music.apple.com",
+    "music.youtube.com",
+    "deezer.com",
+    "tidal.com",
+    "soundcloud.com",
+    "medium.com",
+    "fandom.com",
+    "quora.com",
+    "genius.com",
+    "stackoverflow.com",
+    "imgur.com",
+    "archive.ph",
+    "web.archive.org",
+    "12ft.io",
+];
+
+/// Whether any alternative links can be on the frontend, as matched by [`Link::is_on`].
+pub fn is_known_frontend(frontend: &str) -> bool {
+    let frontends = &config().frontends;
+    let configured = [
+        &frontends.medium,
+        &frontends.fandom,
+        &frontends.quora,
+        &frontends.genius,
+        &frontends.stackoverflow,
+        &frontends.imgur,
+        &frontends.nitter,
+        &frontends.invidious,
+        &frontends.piped,
+    ];
+    ALTERNATIVE_HOSTS
+        .iter()
+        .copied()
+        .chain(configured.into_iter().map(String::as_str))
+        .any(|host| Link::Simple(format!("https://{host}/")).is_on(frontend))
+}
+
+/// Parses a platform link from the given URL, after unwrapping any redirect wrappers around it.
+pub fn parse_platform_link(url: Url) -> Result<PlatformLink, Unsupported> {
+    let (url, unwrapped) = unwrap_redirects(url);
+    match PlatformLink::try_from(url.clone()) {
+        Err(_) if unwrapped => Ok(PlatformLink::RedirectTarget(url.to_string())),
+        result => result,
     }
 }
 
@@ -126,7 +585,7 @@ impl TryFrom<Url> for PlatformLink {
 
     fn try_from(url: Url) -> Result<Self, Self::Error> {
         if url.scheme() != "https" && url.scheme() != "http" {
-            return Err(Unsupported);
+            return Err(Unsupported::Scheme);
         }
 
         match url.domain() {
@@ -146,7 +605,7 @@ impl TryFrom<Url> for PlatformLink {
                     [Some(profile_id), None, _] => {
                         Ok(PlatformLink::InstagramProfile(profile_id.to_string()))
                     }
-                    _ => Err(Unsupported),
+                    _ => Err(Unsupported::Path),
                 }
             }
             Some("youtube.com") | Some("www.youtube.com") if url.path() == "/watch" => {
@@ -165,8 +624,9 @@ impl TryFrom<Url> for PlatformLink {
                         video_id,
                         timestamp,
                     })
-                    .ok_or(Unsupported)
+                    .ok_or(Unsupported::Path)
             }
+            Some("youtube.com") | Some("www.youtube.com") => Err(Unsupported::Path),
             Some("youtu.be") => {
                 if let [Some(video_id), None] = url
                     .path_segments()
@@ -186,7 +646,7 @@ impl TryFrom<Url> for PlatformLink {
                         timestamp,
                     })
                 } else {
-                    Err(Unsupported)
+                    Err(Unsupported::Path)
                 }
             }
             Some("reddit.com") | Some("www.reddit.com") => {
@@ -220,7 +680,7 @@ impl TryFrom<Url> for PlatformLink {
                             comment_id: None,
                         })
                     }
-                    _ => Err(Unsupported),
+                    _ => Err(Unsupported::Path),
                 }
             }
             Some("twitter.com") | Some("www.twitter.com") | Some("x.com") | Some("www.x.com") => {
@@ -232,20 +692,125 @@ impl TryFrom<Url> for PlatformLink {
                 {
                     Ok(PlatformLink::Tweet {
                         username: username.to_string(),
-                        status_id: status_id.parse().map_err(|_| Unsupported)?,
+                        status_id: status_id.parse().map_err(|_| Unsupported::Path)?,
                     })
                 } else {
-                    Err(Unsupported)
+                    Err(Unsupported::Path)
                 }
             }
-            _ => Err(Unsupported),
+            Some("pixiv.net") | Some("www.pixiv.net") => parse_pixiv_link(&url),
+            Some("tumblr.com") | Some("www.tumblr.com") => parse_tumblr_link(&url),
+            Some("clips.twitch.tv") => parse_twitch_clip_link(&url),
+            Some("twitch.tv") | Some("www.twitch.tv") | Some("m.twitch.tv") => {
+                parse_twitch_link(&url)
+            }
+            Some("kick.com") | Some("www.kick.com") => parse_kick_link(&url),
+            Some("vimeo.com") | Some("www.vimeo.com") | Some("player.vimeo.com") => {
+                parse_vimeo_link(&url)
+            }
+            Some("dailymotion.com") | Some("www.dailymotion.com") => parse_dailymotion_link(&url),
+            Some("dai.ly") => parse_dailymotion_short_link(&url),
+            Some("open.spotify.com") => parse_spotify_link(&url),
+            Some("music.apple.com") => parse_apple_music_link(&url),
+            Some("music.youtube.com") => parse_youtube_music_link(&url),
+            Some("deezer.com") | Some("www.deezer.com") => parse_deezer_link(&url),
+            Some("tidal.com") | Some("www.tidal.com") | Some("listen.tidal.com") => {
+                parse_tidal_link(&url)
+            }
+            Some("soundcloud.com") | Some("www.soundcloud.com") | Some("m.soundcloud.com") => {
+                parse_soundcloud_link(&url)
+            }
+            Some("medium.com") | Some("www.medium.com") => parse_medium_link(&url),
+            Some(domain) if domain.ends_with(".medium.com") => parse_medium_link(&url),
+            Some(domain) if domain.ends_with(".fandom.com") => {
+                parse_fandom_link(domain.trim_end_matches(".fandom.com"), &url)
+            }
+            Some("quora.com") | Some("www.quora.com") => parse_quora_link(&url),
+            Some("genius.com") | Some("www.genius.com") => parse_genius_link(&url),
+            Some("stackoverflow.com") | Some("www.stackoverflow.com") => {
+                parse_stackoverflow_link(&url)
+            }
+            Some("imgur.com") | Some("www.imgur.com") | Some("i.imgur.com") => {
+                parse_imgur_link(&url)
+            }
+            Some(domain) if domain.ends_with(".tumblr.com") => {
+                parse_tumblr_subdomain_link(domain.trim_end_matches(".tumblr.com"), &url)
+            }
+            Some("amzn.to") | Some("amzn.eu") | Some("amzn.asia") | Some("a.co") => {
+                Ok(PlatformLink::AmazonShortLink(url.to_string()))
+            }
+            Some(domain) if store_tld(domain, "amazon", AMAZON_TLDS).is_some() => {
+                parse_amazon_link(domain, &url)
+            }
+            Some("aliexpress.com") | Some("aliexpress.us") => parse_aliexpress_link(&url),
+            Some(domain) if domain.ends_with(".aliexpress.com") => parse_aliexpress_link(&url),
+            Some(domain) if store_tld(domain, "ebay", EBAY_TLDS).is_some() => {
+                parse_ebay_link(domain, &url)
+            }
+            Some(domain) if is_news_domain(domain) => parse_article_link(&url),
+            // any other domain might be a fediverse instance
+            Some(domain) => parse_fediverse_link(domain, &url),
+            _ => Err(Unsupported::Domain),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{find_platform_links, PlatformLink};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::config::config;
+
+    use super::{
+        cap_links, find_platform_links, is_known_frontend, join_bounded, Link, MusicKind,
+        MusicService, Platform, PlatformLink, MAX_CONCURRENT_RESOLUTIONS,
+    };
+
+    #[test]
+    fn test_unwrap_redirects() {
+        let message = "
+            https://www.google.com/url?sa=t&url=https%3A%2F%2Fx.com%2Fjohndoe%2Fstatus%2F123456789123456&usg=ZZZZ
+            https://l.facebook.com/l.php?u=https%3A%2F%2Fwww.instagram.com%2Freel%2FAAAAAAAAAAA%2F&h=ZZZZ
+            https://www.youtube.com/redirect?event=video_description&q=https%3A%2F%2Fyoutu.be%2FBBBBBBBBBBB&v=ZZZZ
+            https://steamcommunity.com/linkfilter/?url=https://example.com/some/page
+            https://out.reddit.com/t3_abcdef?url=https%3A%2F%2Fwww.google.com%2Furl%3Fq%3Dhttps%253A%252F%252Fgenius.com%252FSome-song-lyrics&token=ZZZZ
+            https://eur01.safelinks.protection.outlook.com/?url=https%3A%2F%2Fstackoverflow.com%2Fq%2F12345678&data=ZZZZ
+            https://www.google.com/amp/s/www.reddit.com/r/subreddit/comments/AAAAAAA/some_post_name/
+            https://www.google.com/url?q=javascript:alert(1)
+            ";
+
+        let links = find_platform_links(message, &[])
+            .into_iter()
+            .filter_map(|found| found.link.ok())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                PlatformLink::Tweet {
+                    username: "johndoe".to_string(),
+                    status_id: 123456789123456,
+                },
+                PlatformLink::InstagramReel("AAAAAAAAAAA".to_string()),
+                PlatformLink::YoutubeVideo {
+                    video_id: "BBBBBBBBBBB".to_string(),
+                    timestamp: None,
+                },
+                PlatformLink::RedirectTarget("https://example.com/some/page".to_string()),
+                PlatformLink::GeniusSong {
+                    slug: "Some-song-lyrics".to_string(),
+                },
+                PlatformLink::StackOverflowQuestion {
+                    question_id: 12345678,
+                },
+                PlatformLink::RedditPost {
+                    subreddit: "subreddit".to_string(),
+                    post_id: "AAAAAAA".to_string(),
+                    comment_id: None,
+                },
+            ],
+            links
+        )
+    }
 
     #[test]
     fn test_find_platform_links() {
@@ -283,9 +848,91 @@ mod tests {
             Morbi varius augue quis sem efficitur posuere.
             https://x.com/johndoe/status/123456789123456
             https://twitter.com/janedoe/status/988644234135645
+
+            Nulla facilisi.
+            https://www.pixiv.net/en/artworks/12345678
+            https://www.pixiv.net/artworks/23456789#2
+            https://pixiv.net/member_illust.php?mode=medium&illust_id=34567890
+            https://www.pixiv.net/member_illust.php?mode=manga_big&illust_id=45678901&page=2
+            https://www.pixiv.net/member_illust.php?mode=manga_big&illust_id=56789012&page=4294967295
+            https://www.pixiv.net/en/users/12345678
+
+            Vestibulum ante ipsum primis in faucibus.
+            https://someblog.tumblr.com/post/712345678901234567/some-post-slug
+            https://www.tumblr.com/otherblog/723456789012345678
+            https://tumblr.com/otherblog/723456789012345678/some-post-slug?source=share
+            https://www.tumblr.com/tagged/lorem
+
+            Aenean vitae mi eu nisl mattis tempor.
+            https://mastodon.social/@johndoe/112345678901234567
+            https://example.social/@janedoe/112345678901234568?utm_source=share
+            https://mastodon.social/users/johndoe/statuses/112345678901234569
+            https://example.social/users/janedoe/statuses/112345678901234570
+            https://example.com/@janedoe/not-a-post
+
+            Donec sit amet nisi vel lectus ultrices feugiat.
+            https://clips.twitch.tv/SomeClipSlug-AbCdEf
+            https://www.twitch.tv/somechannel/clip/OtherClipSlug?filter=clips
+            https://www.twitch.tv/videos/1234567890?t=1h2m3s
+            https://m.twitch.tv/videos/1234567891
+            https://www.twitch.tv/somechannel
+            https://kick.com/somechannel/clips/clip_01AAAAAAAAAAAAAAAAAAAAAAAA
+            https://kick.com/somechannel?clip=clip_01BBBBBBBBBBBBBBBBBBBBBBBB
+            https://vimeo.com/123456789?share=copy
+            https://vimeo.com/123456780/abcdef1234
+            https://player.vimeo.com/video/123456781?h=fedcba4321
+            https://www.dailymotion.com/video/x8aaaaa
+            https://dai.ly/x8bbbbb
+
+            Quisque in tortor vitae velit cursus pulvinar.
+            https://open.spotify.com/track/AAAAAAAAAAAAAAAAAAAAAA?si=ZZZZZZZZZZZZZZZZ
+            https://open.spotify.com/intl-pt/album/BBBBBBBBBBBBBBBBBBBBBB
+            https://music.apple.com/us/album/some-album/1111111111?i=2222222222
+            https://music.apple.com/pt/playlist/some-playlist/pl.u-CCCCCCCCCCCC
+            https://music.youtube.com/watch?v=DDDDDDDDDDD&si=ZZZZZZZZZZZZZZZZ
+            https://www.deezer.com/en/track/3333333333
+            https://tidal.com/browse/track/44444444
+            https://soundcloud.com/some-artist/some-track?utm_source=clipboard
+            https://soundcloud.com/some-artist/sets/some-playlist
+            https://soundcloud.com/discover/sets/some-playlist
+
+            Integer euismod lacus luctus magna.
+            https://medium.com/@johndoe/some-article-title-1a2b3c4d5e6f?source=rss
+            https://janedoe.medium.com/other-article-title-6f5e4d3c2b1a
+            https://some-wiki.fandom.com/wiki/Some_Page?so=search
+            https://some-wiki.fandom.com/pt-br/wiki/Outra_Pagina
+            https://www.quora.com/What-is-lorem-ipsum?share=1
+            https://www.quora.com/What-is-lorem-ipsum/answer/John-Doe
+            https://genius.com/Some-artist-some-song-lyrics
+            https://stackoverflow.com/questions/12345678/some-question-title
+            https://stackoverflow.com/q/23456789
+            https://imgur.com/a/AbCdEfG
+            https://i.imgur.com/HiJkLmN.jpeg
+            https://imgur.com/upload
+            https://imgur.com/about?ref=footer
+
+            Proin pharetra nonummy pede.
+            https://www.nytimes.com/2024/01/01/world/some-article.html?smid=url-share&utm_source=x
+            https://edition.ft.com/content/some-article?id=1#comments
+            https://www.nytimes.com/
+
+            Cras ornare tristique elit.
+            https://www.amazon.com/Some-Product-Name/dp/B0AAAAAAAA/ref=sr_1_1?crid=ZZZZ&keywords=lorem&tag=affiliate-20
+            https://www.amazon.co.uk/gp/product/B0BBBBBBBB?psc=1
+            https://amazon.de/gp/aw/d/B0CCCCCCCC/?tag=affiliate-21
+            https://amzn.to/3AAAAAA
+            https://www.amazon.com/s?k=lorem
+            https://www.amazon.evil.com/dp/B0DDDDDDDD
+            https://pt.aliexpress.com/item/1005001234567890.html?spm=a2g0o&aff_fcid=ZZZZ
+            https://www.ebay.com/itm/123456789012?mkcid=1&campid=ZZZZ
+            https://www.ebay.co.uk/itm/Some-Item-Name/234567890123
+            https://ebay.co.evil/itm/345678901234
             ";
 
-        let links = find_platform_links(message);
+        let links = find_platform_links(message, &[])
+            .into_iter()
+            .filter_map(|found| found.link.ok())
+            .collect::<Vec<_>>();
 
         assert_eq!(
             vec![
@@ -338,8 +985,316 @@ mod tests {
                     username: "janedoe".to_string(),
                     status_id: 988644234135645,
                 },
+                PlatformLink::PixivArtwork {
+                    id: 12345678,
+                    page: None,
+                },
+                PlatformLink::PixivArtwork {
+                    id: 23456789,
+                    page: Some(2),
+                },
+                PlatformLink::PixivArtwork {
+                    id: 34567890,
+                    page: None,
+                },
+                PlatformLink::PixivArtwork {
+                    id: 45678901,
+                    page: Some(3),
+                },
+                PlatformLink::TumblrPost {
+                    blog: "someblog".to_string(),
+                    post_id: 712345678901234567,
+                },
+                PlatformLink::TumblrPost {
+                    blog: "otherblog".to_string(),
+                    post_id: 723456789012345678,
+                },
+                PlatformLink::TumblrPost {
+                    blog: "otherblog".to_string(),
+                    post_id: 723456789012345678,
+                },
+                PlatformLink::FediversePost {
+                    instance: "mastodon.social".to_string(),
+                    username: "johndoe".to_string(),
+                    post_id: 112345678901234567,
+                },
+                PlatformLink::FediversePost {
+                    instance: "example.social".to_string(),
+                    username: "janedoe".to_string(),
+                    post_id: 112345678901234568,
+                },
+                PlatformLink::FediversePost {
+                    instance: "mastodon.social".to_string(),
+                    username: "johndoe".to_string(),
+                    post_id: 112345678901234569,
+                },
+                PlatformLink::TwitchClip {
+                    slug: "SomeClipSlug-AbCdEf".to_string(),
+                },
+                PlatformLink::TwitchClip {
+                    slug: "OtherClipSlug".to_string(),
+                },
+                PlatformLink::TwitchVod {
+                    video_id: 1234567890,
+                    timestamp: Some(3723),
+                },
+                PlatformLink::TwitchVod {
+                    video_id: 1234567891,
+                    timestamp: None,
+                },
+                PlatformLink::KickClip {
+                    channel: "somechannel".to_string(),
+                    clip_id: "clip_01AAAAAAAAAAAAAAAAAAAAAAAA".to_string(),
+                },
+                PlatformLink::KickClip {
+                    channel: "somechannel".to_string(),
+                    clip_id: "clip_01BBBBBBBBBBBBBBBBBBBBBBBB".to_string(),
+                },
+                PlatformLink::VimeoVideo {
+                    video_id: 123456789,
+                    hash: None,
+                },
+                PlatformLink::VimeoVideo {
+                    video_id: 123456780,
+                    hash: Some("abcdef1234".to_string()),
+                },
+                PlatformLink::VimeoVideo {
+                    video_id: 123456781,
+                    hash: Some("fedcba4321".to_string()),
+                },
+                PlatformLink::DailymotionVideo {
+                    video_id: "x8aaaaa".to_string(),
+                },
+                PlatformLink::DailymotionVideo {
+                    video_id: "x8bbbbb".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::Spotify,
+                    kind: MusicKind::Track,
+                    id: "AAAAAAAAAAAAAAAAAAAAAA".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::Spotify,
+                    kind: MusicKind::Album,
+                    id: "BBBBBBBBBBBBBBBBBBBBBB".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::AppleMusic,
+                    kind: MusicKind::Track,
+                    id: "2222222222".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::AppleMusic,
+                    kind: MusicKind::Playlist,
+                    id: "pl.u-CCCCCCCCCCCC".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::YoutubeMusic,
+                    kind: MusicKind::Track,
+                    id: "DDDDDDDDDDD".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::Deezer,
+                    kind: MusicKind::Track,
+                    id: "3333333333".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::Tidal,
+                    kind: MusicKind::Track,
+                    id: "44444444".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::SoundCloud,
+                    kind: MusicKind::Track,
+                    id: "some-artist/some-track".to_string(),
+                },
+                PlatformLink::Music {
+                    service: MusicService::SoundCloud,
+                    kind: MusicKind::Playlist,
+                    id: "some-artist/sets/some-playlist".to_string(),
+                },
+                PlatformLink::MediumArticle {
+                    post_id: "1a2b3c4d5e6f".to_string(),
+                },
+                PlatformLink::MediumArticle {
+                    post_id: "6f5e4d3c2b1a".to_string(),
+                },
+                PlatformLink::FandomPage {
+                    wiki: "some-wiki".to_string(),
+                    lang: None,
+                    page: "Some_Page".to_string(),
+                },
+                PlatformLink::FandomPage {
+                    wiki: "some-wiki".to_string(),
+                    lang: Some("pt-br".to_string()),
+                    page: "Outra_Pagina".to_string(),
+                },
+                PlatformLink::QuoraPage {
+                    path: "What-is-lorem-ipsum".to_string(),
+                },
+                PlatformLink::QuoraPage {
+                    path: "What-is-lorem-ipsum/answer/John-Doe".to_string(),
+                },
+                PlatformLink::GeniusSong {
+                    slug: "Some-artist-some-song-lyrics".to_string(),
+                },
+                PlatformLink::StackOverflowQuestion {
+                    question_id: 12345678,
+                },
+                PlatformLink::StackOverflowQuestion {
+                    question_id: 23456789,
+                },
+                PlatformLink::ImgurPost {
+                    path: "a/AbCdEfG".to_string(),
+                },
+                PlatformLink::ImgurPost {
+                    path: "HiJkLmN.jpeg".to_string(),
+                },
+                PlatformLink::Article {
+                    url: "https://www.nytimes.com/2024/01/01/world/some-article.html".to_string(),
+                },
+                PlatformLink::Article {
+                    url: "https://edition.ft.com/content/some-article?id=1".to_string(),
+                },
+                PlatformLink::AmazonProduct {
+                    tld: "com".to_string(),
+                    asin: "B0AAAAAAAA".to_string(),
+                },
+                PlatformLink::AmazonProduct {
+                    tld: "co.uk".to_string(),
+                    asin: "B0BBBBBBBB".to_string(),
+                },
+                PlatformLink::AmazonProduct {
+                    tld: "de".to_string(),
+                    asin: "B0CCCCCCCC".to_string(),
+                },
+                PlatformLink::AmazonShortLink("https://amzn.to/3AAAAAA".to_string()),
+                PlatformLink::AliExpressProduct {
+                    item_id: 1005001234567890,
+                },
+                PlatformLink::EbayProduct {
+                    tld: "com".to_string(),
+                    item_id: 123456789012,
+                },
+                PlatformLink::EbayProduct {
+                    tld: "co.uk".to_string(),
+                    item_id: 234567890123,
+                },
             ],
             links
         )
     }
+
+    #[test]
+    fn test_same_as() {
+        let links = find_platform_links(
+            "https://twitter.com/johndoe/status/123 https://x.com/i/status/123 \
+            https://reddit.com/r/rust/comments/abc123/title https://www.reddit.com/r/Rust/comments/abc123 \
+            https://www.reddit.com/r/rust/comments/abc123/comment/def456",
+            &[],
+        );
+
+        assert!(links[4].link.is_ok());
+        assert!(links[0].same_as(&links[1]));
+        assert!(links[2].same_as(&links[3]));
+        assert!(!links[3].same_as(&links[4]));
+    }
+
+    #[test]
+    fn test_cap_links() {
+        let max = config().max_links_per_invocation;
+        let message = (0..max + 2)
+            .map(|i| format!("https://x.com/johndoe/status/{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut links = find_platform_links(&message, &[]);
+
+        assert_eq!(2, cap_links(&mut links));
+        assert_eq!(max, links.len());
+        assert_eq!("https://x.com/johndoe/status/0", links[0].url);
+        assert_eq!(0, cap_links(&mut links));
+    }
+
+    #[tokio::test]
+    async fn test_join_bounded() {
+        let running = AtomicUsize::new(0);
+        let most_running = AtomicUsize::new(0);
+        let outputs = join_bounded((0..10).map(|i| {
+            let (running, most_running) = (&running, &most_running);
+            async move {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most_running.fetch_max(now_running, Ordering::SeqCst);
+                // later futures finish first, which does not change the order of the outputs
+                for _ in 0..10 - i {
+                    tokio::task::yield_now().await;
+                }
+                running.fetch_sub(1, Ordering::SeqCst);
+                i
+            }
+        }))
+        .await;
+
+        assert_eq!((0..10).collect::<Vec<_>>(), outputs);
+        assert_eq!(
+            MAX_CONCURRENT_RESOLUTIONS,
+            most_running.load(Ordering::SeqCst)
+        );
+    }
+
+    #[test]
+    fn test_is_on() {
+        let link = Link::Simple("https://old.reddit.com/r/rust/comments/abc123".to_string());
+
+        assert!(link.is_on("old.reddit"));
+        assert!(link.is_on("Reddit"));
+        assert!(link.is_on("old.reddit.com"));
+        assert!(!link.is_on("e"));
+        assert!(!link.is_on("old.red"));
+        assert!(!link.is_on(".com"));
+        assert!(!link.is_on(""));
+
+        let link = Link::Embed("https://fxtwitter.com/a/status/1".to_string());
+        assert!(link.is_on("fxtwitter"));
+        assert!(!link.is_on("x"));
+        assert!(!link.is_on("twitter"));
+    }
+
+    #[test]
+    fn test_is_known_frontend() {
+        assert!(is_known_frontend("fxtwitter"));
+        assert!(is_known_frontend("old.reddit"));
+        assert!(is_known_frontend(&config().frontends.nitter));
+        assert!(!is_known_frontend("twitter"));
+        assert!(!is_known_frontend("example"));
+        assert!(!is_known_frontend(&"a".repeat(1000)));
+    }
+
+    #[test]
+    fn test_disabled_platforms() {
+        let message = "https://x.com/johndoe/status/123 https://youtu.be/AAAAAAAAAAA \
+            https://www.google.com/url?url=https%3A%2F%2Fx.com%2Fjohndoe%2Fstatus%2F456 \
+            https://example.com/page https://x.com/johndoe https://www.youtube.com/@channel";
+
+        let links = find_platform_links(message, &[Platform::Twitter])
+            .into_iter()
+            .map(|found| found.link.map(|link| link.platform()).ok())
+            .collect::<Vec<_>>();
+
+        // unsupported pages of disabled platforms are left out too, instead of failing
+        assert_eq!(vec![Some(Platform::Youtube), None, None], links);
+
+        let links = find_platform_links(message, &[Platform::Youtube])
+            .into_iter()
+            .map(|found| found.url)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "https://x.com/johndoe/status/123",
+                "https://www.google.com/url?url=https%3A%2F%2Fx.com%2Fjohndoe%2Fstatus%2F456",
+                "https://example.com/page",
+                "https://x.com/johndoe",
+            ],
+            links
+        );
+    }
 }
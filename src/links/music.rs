@@ -0,0 +1,340 @@
+// Specific logic for music streaming services, since equivalent links on other services
+// are looked up through an Odesli (song.link) compatible API
+
+use std::collections::HashMap;
+
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use crate::config::config;
+
+use super::{
+    error::check_status, Link, PlatformLink, ResolutionError, Unsupported, RESOLUTION_TIMEOUT,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MusicService {
+    Spotify,
+    AppleMusic,
+    YoutubeMusic,
+    Deezer,
+    Tidal,
+    SoundCloud,
+}
+
+impl MusicService {
+    const ALL: [MusicService; 6] = [
+        MusicService::Spotify,
+        MusicService::AppleMusic,
+        MusicService::YoutubeMusic,
+        MusicService::Deezer,
+        MusicService::Tidal,
+        MusicService::SoundCloud,
+    ];
+
+    /// The key used by Odesli in `linksByPlatform`.
+    fn odesli_platform(&self) -> &'static str {
+        match self {
+            MusicService::Spotify => "spotify",
+            MusicService::AppleMusic => "appleMusic",
+            MusicService::YoutubeMusic => "youtubeMusic",
+            MusicService::Deezer => "deezer",
+            MusicService::Tidal => "tidal",
+            MusicService::SoundCloud => "soundcloud",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MusicKind {
+    Track,
+    Album,
+    Playlist,
+    Artist,
+}
+
+impl MusicKind {
+    fn from_path(segment: &str) -> Option<Self> {
+        match segment {
+            "track" | "song" => Some(MusicKind::Track),
+            "album" => Some(MusicKind::Album),
+            "playlist" => Some(MusicKind::Playlist),
+            "artist" => Some(MusicKind::Artist),
+            _ => None,
+        }
+    }
+
+    fn as_path(&self) -> &'static str {
+        match self {
+            MusicKind::Track => "track",
+            MusicKind::Album => "album",
+            MusicKind::Playlist => "playlist",
+            MusicKind::Artist => "artist",
+        }
+    }
+}
+
+fn music_link(service: MusicService, kind: MusicKind, id: &str) -> PlatformLink {
+    PlatformLink::Music {
+        service,
+        kind,
+        id: id.to_string(),
+    }
+}
+
+/// Parses `open.spotify.com/(intl-<lang>/)<kind>/<id>` links.
+pub fn parse_spotify_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|it| it.skip_while(|s| s.starts_with("intl-")))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        [Some(kind), Some(id), None] => MusicKind::from_path(kind)
+            .map(|kind| music_link(MusicService::Spotify, kind, id))
+            .ok_or(Unsupported::Path),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `music.apple.com/<country>/<kind>/<perhaps slug>/<id>` links,
+/// where tracks are linked through their album with `?i=<track id>`.
+pub fn parse_apple_music_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    let track_id = url
+        .query_pairs()
+        .find(|(key, _)| key == "i")
+        .map(|(_, id)| id.to_string());
+
+    let (kind, id) = match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 5])
+    {
+        [Some(_), Some(kind), Some(_), Some(id), None]
+        | [Some(_), Some(kind), Some(id), None, _] => (kind, id),
+        _ => return Err(Unsupported::Path),
+    };
+
+    match (MusicKind::from_path(kind), track_id) {
+        (Some(MusicKind::Album), Some(track_id)) => Ok(music_link(
+            MusicService::AppleMusic,
+            MusicKind::Track,
+            &track_id,
+        )),
+        (Some(kind), _) => Ok(music_link(MusicService::AppleMusic, kind, id)),
+        (None, _) => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `music.youtube.com/watch?v=<id>`, `/playlist?list=<id>` and `/channel/<id>` links.
+pub fn parse_youtube_music_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    let query_param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        [Some("watch"), None, _] => query_param("v")
+            .map(|id| music_link(MusicService::YoutubeMusic, MusicKind::Track, &id))
+            .ok_or(Unsupported::Path),
+        [Some("playlist"), None, _] => query_param("list")
+            .map(|id| music_link(MusicService::YoutubeMusic, MusicKind::Playlist, &id))
+            .ok_or(Unsupported::Path),
+        [Some("channel"), Some(id), None] => Ok(music_link(
+            MusicService::YoutubeMusic,
+            MusicKind::Artist,
+            id,
+        )),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `deezer.com/(<lang>/)<kind>/<id>` links.
+pub fn parse_deezer_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        [Some(kind), Some(id), None, _] | [Some(_), Some(kind), Some(id), None] => {
+            MusicKind::from_path(kind)
+                .filter(|_| id.bytes().all(|b| b.is_ascii_digit()))
+                .map(|kind| music_link(MusicService::Deezer, kind, id))
+                .ok_or(Unsupported::Path)
+        }
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `tidal.com/(browse/)<kind>/<id>` links.
+pub fn parse_tidal_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|it| it.skip_while(|s| *s == "browse"))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        [Some(kind), Some(id)] => MusicKind::from_path(kind)
+            .map(|kind| music_link(MusicService::Tidal, kind, id))
+            .ok_or(Unsupported::Path),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `soundcloud.com/<artist>/<track>` and `soundcloud.com/<artist>/sets/<playlist>` links.
+pub fn parse_soundcloud_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    const RESERVED: &[&str] = &["discover", "search", "stream", "you", "charts", "upload"];
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        [Some(artist), ..] if RESERVED.contains(&artist) => Err(Unsupported::Path),
+        [Some(artist), Some("sets"), Some(playlist), None] => Ok(music_link(
+            MusicService::SoundCloud,
+            MusicKind::Playlist,
+            &format!("{artist}/sets/{playlist}"),
+        )),
+        [Some(artist), Some(track), None, _] => Ok(music_link(
+            MusicService::SoundCloud,
+            MusicKind::Track,
+            &format!("{artist}/{track}"),
+        )),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+fn canonical_music_url(service: MusicService, kind: MusicKind, id: &str) -> String {
+    match (service, kind) {
+        (MusicService::Spotify, kind) => {
+            format!("https://open.spotify.com/{}/{id}", kind.as_path())
+        }
+        (MusicService::AppleMusic, MusicKind::Track) => {
+            format!("https://music.apple.com/song/{id}")
+        }
+        (MusicService::AppleMusic, kind) => {
+            format!("https://music.apple.com/{}/{id}", kind.as_path())
+        }
+        (MusicService::YoutubeMusic, MusicKind::Track) => {
+            format!("https://music.youtube.com/watch?v={id}")
+        }
+        (MusicService::YoutubeMusic, MusicKind::Artist) => {
+            format!("https://music.youtube.com/channel/{id}")
+        }
+        (MusicService::YoutubeMusic, _) => format!("https://music.youtube.com/playlist?list={id}"),
+        (MusicService::Deezer, kind) => format!("https://www.deezer.com/{}/{id}", kind.as_path()),
+        (MusicService::Tidal, kind) => format!("https://tidal.com/browse/{}/{id}", kind.as_path()),
+        (MusicService::SoundCloud, _) => format!("https://soundcloud.com/{id}"),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OdesliResponse {
+    page_url: String,
+    links_by_platform: HashMap<String, OdesliPlatformLink>,
+}
+
+#[derive(Deserialize)]
+struct OdesliPlatformLink {
+    url: String,
+}
+
+async fn resolve_odesli_links(
+    api_url: &str,
+    music_url: &str,
+) -> Result<OdesliResponse, ResolutionError> {
+    let api_url = Url::parse_with_params(
+        &format!("{}/links", api_url.trim_end_matches('/')),
+        &[("url", music_url)],
+    )
+    .map_err(|_| ResolutionError::Failed)?;
+
+    let client = Client::builder().timeout(RESOLUTION_TIMEOUT).build()?;
+    let response = check_status(client.get(api_url).send().await?)?;
+    Ok(response.json().await?)
+}
+
+/// Picks the links on every other supported service, in a stable order.
+fn other_service_links(response: &OdesliResponse, service: MusicService) -> Vec<Link> {
+    MusicService::ALL
+        .into_iter()
+        .filter(|other| *other != service)
+        .filter_map(|other| response.links_by_platform.get(other.odesli_platform()))
+        .map(|link| Link::Simple(link.url.clone()))
+        .chain([Link::Simple(response.page_url.clone())])
+        .collect()
+}
+
+pub async fn alternative_music_links(
+    service: MusicService,
+    kind: MusicKind,
+    id: &str,
+) -> Vec<Link> {
+    let music_url = canonical_music_url(service, kind, id);
+
+    // the canonical link is still worth showing when the other services cannot be looked up
+    let mut links = vec![Link::Simple(music_url.clone())];
+    match resolve_odesli_links(&config().odesli_api_url, &music_url).await {
+        Ok(response) => links.extend(other_service_links(&response, service)),
+        Err(e) => println!("failed to look up {music_url} on other services: {e}"),
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{other_service_links, MusicService, OdesliPlatformLink, OdesliResponse};
+
+    #[test]
+    fn test_other_service_links() {
+        let response = OdesliResponse {
+            page_url: "https://song.link/s/AAAA".to_string(),
+            links_by_platform: HashMap::from(
+                [
+                    ("spotify", "https://open.spotify.com/track/AAAA"),
+                    ("deezer", "https://www.deezer.com/track/1234"),
+                    ("appleMusic", "https://music.apple.com/us/album/a/1?i=2"),
+                    ("amazonMusic", "https://music.amazon.com/albums/B000"),
+                ]
+                .map(|(platform, url)| {
+                    (
+                        platform.to_string(),
+                        OdesliPlatformLink {
+                            url: url.to_string(),
+                        },
+                    )
+                }),
+            ),
+        };
+
+        let links = other_service_links(&response, MusicService::Spotify)
+            .into_iter()
+            .map(|link| link.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "<https://music.apple.com/us/album/a/1?i=2>",
+                "<https://www.deezer.com/track/1234>",
+                "<https://song.link/s/AAAA>",
+            ],
+            links
+        );
+    }
+}
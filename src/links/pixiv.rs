@@ -0,0 +1,67 @@
+// Specific logic for Pixiv, since artworks can be linked through multiple URL shapes
+
+use std::borrow::Cow;
+
+use url::Url;
+
+use super::{Link, PlatformLink, Unsupported};
+
+pub fn parse_pixiv_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    // pages are 1-indexed in pixiv's own "#<page>" fragments
+    let fragment_page = url.fragment().and_then(|f| f.parse::<u32>().ok());
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [None; 4].map(|_: Option<&str>| it.next()))
+        .unwrap_or([None; 4])
+    {
+        // /artworks/<id>/<perhaps page>
+        // /en/artworks/<id>/<perhaps page>
+        [Some("artworks"), Some(id), page, None] | [Some(_), Some("artworks"), Some(id), page] => {
+            Ok(PlatformLink::PixivArtwork {
+                id: id.parse().map_err(|_| Unsupported::Path)?,
+                page: page.and_then(|p| p.parse().ok()).or(fragment_page),
+            })
+        }
+        // /member_illust.php?illust_id=<id>
+        [Some("member_illust.php"), None, ..] => {
+            let mut id = None;
+            let mut page = None;
+            for (key, value) in url.query_pairs() {
+                match key {
+                    Cow::Borrowed("illust_id") => id = value.parse().ok(),
+                    // legacy manga pages are 0-indexed
+                    Cow::Borrowed("page") => {
+                        page = match value.parse::<u32>() {
+                            Ok(p) => Some(p.checked_add(1).ok_or(Unsupported::Path)?),
+                            Err(_) => None,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            id.map(|id| PlatformLink::PixivArtwork {
+                id,
+                page: page.or(fragment_page),
+            })
+            .ok_or(Unsupported::Path)
+        }
+        _ => Err(Unsupported::Path),
+    }
+}
+
+pub fn alternative_pixiv_links(id: u64, page: Option<u32>) -> Vec<Link> {
+    if let Some(page) = page {
+        vec![
+            Link::Embed(format!("https://www.phixiv.net/artworks/{id}/{page}")),
+            Link::Simple(format!("https://www.pixiv.net/artworks/{id}#{page}")),
+        ]
+    } else {
+        vec![
+            Link::Embed(format!("https://www.phixiv.net/artworks/{id}")),
+            Link::Simple(format!("https://www.pixiv.net/artworks/{id}")),
+        ]
+    }
+}
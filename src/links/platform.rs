@@ -0,0 +1,200 @@
+// Families of platform links, which can be turned off as a whole
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::config,
+    i18n::{Language, Localize},
+};
+
+use super::{
+    article::is_news_domain,
+    shopping::{store_tld, AMAZON_TLDS, EBAY_TLDS},
+    PlatformLink,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    Instagram,
+    Youtube,
+    Reddit,
+    Twitter,
+    Pixiv,
+    Tumblr,
+    Fediverse,
+    Twitch,
+    Kick,
+    Vimeo,
+    Dailymotion,
+    Music,
+    Medium,
+    Fandom,
+    Quora,
+    Genius,
+    StackOverflow,
+    Imgur,
+    News,
+    Amazon,
+    AliExpress,
+    Ebay,
+    Redirects,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 23] = [
+        Platform::Instagram,
+        Platform::Youtube,
+        Platform::Reddit,
+        Platform::Twitter,
+        Platform::Pixiv,
+        Platform::Tumblr,
+        Platform::Fediverse,
+        Platform::Twitch,
+        Platform::Kick,
+        Platform::Vimeo,
+        Platform::Dailymotion,
+        Platform::Music,
+        Platform::Medium,
+        Platform::Fandom,
+        Platform::Quora,
+        Platform::Genius,
+        Platform::StackOverflow,
+        Platform::Imgur,
+        Platform::News,
+        Platform::Amazon,
+        Platform::AliExpress,
+        Platform::Ebay,
+        Platform::Redirects,
+    ];
+
+    /// Identifier of the platform in commands and settings, like `stack_overflow`.
+    pub fn id(self) -> &'static str {
+        match self {
+            Platform::Instagram => "instagram",
+            Platform::Youtube => "youtube",
+            Platform::Reddit => "reddit",
+            Platform::Twitter => "twitter",
+            Platform::Pixiv => "pixiv",
+            Platform::Tumblr => "tumblr",
+            Platform::Fediverse => "fediverse",
+            Platform::Twitch => "twitch",
+            Platform::Kick => "kick",
+            Platform::Vimeo => "vimeo",
+            Platform::Dailymotion => "dailymotion",
+            Platform::Music => "music",
+            Platform::Medium => "medium",
+            Platform::Fandom => "fandom",
+            Platform::Quora => "quora",
+            Platform::Genius => "genius",
+            Platform::StackOverflow => "stack_overflow",
+            Platform::Imgur => "imgur",
+            Platform::News => "news",
+            Platform::Amazon => "amazon",
+            Platform::AliExpress => "ali_express",
+            Platform::Ebay => "ebay",
+            Platform::Redirects => "redirects",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Platform> {
+        Platform::ALL
+            .into_iter()
+            .find(|platform| platform.id() == id)
+    }
+
+    /// The platform of the links on the domain, even of the kinds of pages that are not
+    /// supported, following the domains that [`PlatformLink`] is parsed from.
+    pub fn from_domain(domain: &str) -> Option<Platform> {
+        match domain {
+            "instagram.com" | "www.instagram.com" => Some(Platform::Instagram),
+            "youtube.com" | "www.youtube.com" | "youtu.be" => Some(Platform::Youtube),
+            "reddit.com" | "www.reddit.com" => Some(Platform::Reddit),
+            "twitter.com" | "www.twitter.com" | "x.com" | "www.x.com" => Some(Platform::Twitter),
+            "pixiv.net" | "www.pixiv.net" => Some(Platform::Pixiv),
+            "tumblr.com" | "www.tumblr.com" => Some(Platform::Tumblr),
+            "clips.twitch.tv" | "twitch.tv" | "www.twitch.tv" | "m.twitch.tv" => {
+                Some(Platform::Twitch)
+            }
+            "kick.com" | "www.kick.com" => Some(Platform::Kick),
+            "vimeo.com" | "www.vimeo.com" | "player.vimeo.com" => Some(Platform::Vimeo),
+            "dailymotion.com" | "www.dailymotion.com" | "dai.ly" => Some(Platform::Dailymotion),
+            "open.spotify.com" | "music.apple.com" | "music.youtube.com" | "deezer.com"
+            | "www.deezer.com" | "tidal.com" | "www.tidal.com" | "listen.tidal.com"
+            | "soundcloud.com" | "www.soundcloud.com" | "m.soundcloud.com" => Some(Platform::Music),
+            "medium.com" | "www.medium.com" => Some(Platform::Medium),
+            domain if domain.ends_with(".medium.com") => Some(Platform::Medium),
+            domain if domain.ends_with(".fandom.com") => Some(Platform::Fandom),
+            "quora.com" | "www.quora.com" => Some(Platform::Quora),
+            "genius.com" | "www.genius.com" => Some(Platform::Genius),
+            "stackoverflow.com" | "www.stackoverflow.com" => Some(Platform::StackOverflow),
+            "imgur.com" | "www.imgur.com" | "i.imgur.com" => Some(Platform::Imgur),
+            domain if domain.ends_with(".tumblr.com") => Some(Platform::Tumblr),
+            "amzn.to" | "amzn.eu" | "amzn.asia" | "a.co" => Some(Platform::Amazon),
+            domain if store_tld(domain, "amazon", AMAZON_TLDS).is_some() => Some(Platform::Amazon),
+            "aliexpress.com" | "aliexpress.us" => Some(Platform::AliExpress),
+            domain if domain.ends_with(".aliexpress.com") => Some(Platform::AliExpress),
+            domain if store_tld(domain, "ebay", EBAY_TLDS).is_some() => Some(Platform::Ebay),
+            domain if is_news_domain(domain) => Some(Platform::News),
+            domain
+                if config()
+                    .fediverse_instances
+                    .iter()
+                    .any(|known| known == domain) =>
+            {
+                Some(Platform::Fediverse)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Localize for Platform {
+    fn localize(&self, language: Language) -> String {
+        language.tr(&format!("platform.{}", self.id()))
+    }
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+impl PlatformLink {
+    pub fn platform(&self) -> Platform {
+        match self {
+            PlatformLink::InstagramReel(_)
+            | PlatformLink::InstagramPost(_)
+            | PlatformLink::InstagramProfile(_) => Platform::Instagram,
+            PlatformLink::YoutubeVideo { .. } => Platform::Youtube,
+            PlatformLink::RedditShareLink { .. } | PlatformLink::RedditPost { .. } => {
+                Platform::Reddit
+            }
+            PlatformLink::Tweet { .. } => Platform::Twitter,
+            PlatformLink::PixivArtwork { .. } => Platform::Pixiv,
+            PlatformLink::TumblrPost { .. } => Platform::Tumblr,
+            PlatformLink::FediversePost { .. } => Platform::Fediverse,
+            PlatformLink::TwitchClip { .. } | PlatformLink::TwitchVod { .. } => Platform::Twitch,
+            PlatformLink::KickClip { .. } => Platform::Kick,
+            PlatformLink::VimeoVideo { .. } => Platform::Vimeo,
+            PlatformLink::DailymotionVideo { .. } => Platform::Dailymotion,
+            PlatformLink::Music { .. } => Platform::Music,
+            PlatformLink::MediumArticle { .. } => Platform::Medium,
+            PlatformLink::FandomPage { .. } => Platform::Fandom,
+            PlatformLink::QuoraPage { .. } => Platform::Quora,
+            PlatformLink::GeniusSong { .. } => Platform::Genius,
+            PlatformLink::StackOverflowQuestion { .. } => Platform::StackOverflow,
+            PlatformLink::ImgurPost { .. } => Platform::Imgur,
+            PlatformLink::Article { .. } => Platform::News,
+            PlatformLink::AmazonProduct { .. } | PlatformLink::AmazonShortLink(_) => {
+                Platform::Amazon
+            }
+            PlatformLink::AliExpressProduct { .. } => Platform::AliExpress,
+            PlatformLink::EbayProduct { .. } => Platform::Ebay,
+            PlatformLink::RedirectTarget(_) => Platform::Redirects,
+        }
+    }
+}
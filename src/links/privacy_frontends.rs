@@ -0,0 +1,199 @@
+// Specific logic for text-heavy sites that have well-known privacy frontends
+
+use url::Url;
+
+use crate::config::config;
+
+use super::{Link, PlatformLink, Unsupported};
+
+/// Parses `medium.com/<author or publication>/<slug>-<id>` and `<author>.medium.com/<slug>-<id>` links.
+pub fn parse_medium_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    let post_slug = match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        // /p/<id>
+        [Some("p"), Some(post_id), None] => post_id,
+        // /<author or publication>/<slug>-<id>
+        [Some(_), Some(slug), None] => slug,
+        // /<slug>-<id> (on an author's subdomain)
+        [Some(slug), None, _] if url.domain() != Some("medium.com") => slug,
+        _ => return Err(Unsupported::Path),
+    };
+
+    // post ids are the hexadecimal suffix of the slug
+    let post_id = post_slug.rsplit('-').next().unwrap_or(post_slug);
+    if post_id.len() < 8 || !post_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Unsupported::Path);
+    }
+
+    Ok(PlatformLink::MediumArticle {
+        post_id: post_id.to_string(),
+    })
+}
+
+/// Parses `<wiki>.fandom.com/(<lang>/)wiki/<page>` links.
+pub fn parse_fandom_link(wiki: &str, url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        [Some("wiki"), Some(page), None, _] => Ok(PlatformLink::FandomPage {
+            wiki: wiki.to_string(),
+            lang: None,
+            page: page.to_string(),
+        }),
+        [Some(lang), Some("wiki"), Some(page), None] => Ok(PlatformLink::FandomPage {
+            wiki: wiki.to_string(),
+            lang: Some(lang.to_string()),
+            page: page.to_string(),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `quora.com/<question>` and `quora.com/<question>/answer/<author>` links.
+pub fn parse_quora_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    const RESERVED: &[&str] = &["search", "spaces", "topic", "profile", "settings"];
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        [Some(question), ..] if RESERVED.contains(&question) => Err(Unsupported::Path),
+        [Some(question), None, ..] => Ok(PlatformLink::QuoraPage {
+            path: question.to_string(),
+        }),
+        [Some(question), Some("answer"), Some(author), None] => Ok(PlatformLink::QuoraPage {
+            path: format!("{question}/answer/{author}"),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `genius.com/<artist>-<song>-lyrics` links.
+pub fn parse_genius_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        [Some(slug), None] if slug.ends_with("-lyrics") => Ok(PlatformLink::GeniusSong {
+            slug: slug.to_string(),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `stackoverflow.com/questions/<id>/<perhaps slug>` and `stackoverflow.com/q/<id>` links.
+pub fn parse_stackoverflow_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        [Some("questions"), Some(question_id)] | [Some("q"), Some(question_id)] => {
+            Ok(PlatformLink::StackOverflowQuestion {
+                question_id: question_id.parse().map_err(|_| Unsupported::Path)?,
+            })
+        }
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `imgur.com/<id>`, `imgur.com/a/<id>`, `imgur.com/gallery/<id>` and `i.imgur.com/<id>.<ext>` links.
+pub fn parse_imgur_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    const RESERVED: &[&str] = &[
+        "upload", "about", "signin", "register", "search", "user", "t", "hot", "new", "top",
+        "rules", "privacy", "tos", "apps", "emerald", "account", "settings",
+    ];
+    let is_id = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric());
+    // direct media have the file extension after the id
+    let is_post = |file: &str| match file.split_once('.') {
+        Some((id, extension)) => is_id(id) && is_id(extension),
+        None => is_id(file),
+    };
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        [Some(kind @ ("a" | "gallery")), Some(id), None] => Ok(PlatformLink::ImgurPost {
+            path: format!("{kind}/{id}"),
+        }),
+        [Some(id), None, _] if RESERVED.contains(&id) => Err(Unsupported::Path),
+        [Some(file), None, _] if is_post(file) => Ok(PlatformLink::ImgurPost {
+            path: file.to_string(),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+pub fn alternative_medium_links(post_id: &str) -> Vec<Link> {
+    vec![
+        Link::Simple(format!("https://{}/p/{post_id}", config().frontends.medium)),
+        Link::Simple(format!("https://medium.com/p/{post_id}")),
+    ]
+}
+
+pub fn alternative_fandom_links(wiki: &str, lang: Option<&str>, page: &str) -> Vec<Link> {
+    let frontend = &config().frontends.fandom;
+    if let Some(lang) = lang {
+        vec![
+            Link::Simple(format!("https://{frontend}/{wiki}/{lang}/wiki/{page}")),
+            Link::Simple(format!("https://{wiki}.fandom.com/{lang}/wiki/{page}")),
+        ]
+    } else {
+        vec![
+            Link::Simple(format!("https://{frontend}/{wiki}/wiki/{page}")),
+            Link::Simple(format!("https://{wiki}.fandom.com/wiki/{page}")),
+        ]
+    }
+}
+
+pub fn alternative_quora_links(path: &str) -> Vec<Link> {
+    vec![
+        Link::Simple(format!("https://{}/{path}", config().frontends.quora)),
+        Link::Simple(format!("https://www.quora.com/{path}")),
+    ]
+}
+
+pub fn alternative_genius_links(slug: &str) -> Vec<Link> {
+    vec![
+        Link::Simple(format!("https://{}/{slug}", config().frontends.genius)),
+        Link::Simple(format!("https://genius.com/{slug}")),
+    ]
+}
+
+pub fn alternative_stackoverflow_links(question_id: u64) -> Vec<Link> {
+    vec![
+        Link::Simple(format!(
+            "https://{}/questions/{question_id}",
+            config().frontends.stackoverflow
+        )),
+        Link::Simple(format!("https://stackoverflow.com/questions/{question_id}")),
+    ]
+}
+
+pub fn alternative_imgur_links(path: &str) -> Vec<Link> {
+    // direct media are only served from the `i.` subdomain
+    let host = if path.contains('.') {
+        "i.imgur.com"
+    } else {
+        "imgur.com"
+    };
+    vec![
+        Link::Simple(format!("https://{}/{path}", config().frontends.imgur)),
+        Link::Simple(format!("https://{host}/{path}")),
+    ]
+}
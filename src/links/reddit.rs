@@ -3,28 +3,35 @@
 use reqwest::{header::LOCATION, redirect, Client};
 use url::Url;
 
-use super::{Link, PlatformLink};
+use super::{error::check_status, Link, PlatformLink, ResolutionError, RESOLUTION_TIMEOUT};
 
-pub async fn resolve_reddit_share_link(subreddit: &str, share_id: &str) -> Option<PlatformLink> {
+pub async fn resolve_reddit_share_link(
+    subreddit: &str,
+    share_id: &str,
+) -> Result<PlatformLink, ResolutionError> {
     let client = Client::builder()
         .redirect(redirect::Policy::none())
         .user_agent("curl/8.7.1") // otherwise endpoint returns 403
-        .build()
-        .ok()?;
+        .timeout(RESOLUTION_TIMEOUT)
+        .build()?;
 
     let response = client
         .get(format!("https://www.reddit.com/r/{subreddit}/s/{share_id}"))
         .send()
-        .await
-        .ok()?;
-    let real_link = response.headers().get(LOCATION)?;
-    let real_link = Url::parse(real_link.to_str().ok()?).ok()?;
-    dbg!(&real_link);
+        .await?;
+    let response = check_status(response)?;
+    let real_link = response
+        .headers()
+        .get(LOCATION)
+        .and_then(|real_link| real_link.to_str().ok())
+        .and_then(|real_link| Url::parse(real_link).ok())
+        .ok_or(ResolutionError::Failed)?;
 
     // filter to avoid infinite recursion
     PlatformLink::try_from(real_link)
         .ok()
         .filter(|pl| matches!(pl, PlatformLink::RedditPost { .. }))
+        .ok_or(ResolutionError::Failed)
 }
 
 pub fn alternative_reddit_links(
@@ -0,0 +1,70 @@
+// Offline unwrapping of redirect wrappers, which hide the real URL in a query parameter or path
+
+use url::Url;
+
+/// Wrappers are unwrapped recursively, up to this many times.
+const MAX_UNWRAP_DEPTH: usize = 5;
+
+/// Returns the innermost URL behind any known redirect wrappers,
+/// and whether anything was unwrapped at all.
+pub fn unwrap_redirects(url: Url) -> (Url, bool) {
+    let mut url = url;
+    let mut unwrapped = false;
+    for _ in 0..MAX_UNWRAP_DEPTH {
+        match unwrap_redirect(&url) {
+            Some(inner) => {
+                url = inner;
+                unwrapped = true;
+            }
+            None => break,
+        }
+    }
+    (url, unwrapped)
+}
+
+fn unwrap_redirect(url: &Url) -> Option<Url> {
+    let domain = url.domain()?;
+    let path = url.path();
+
+    let inner = match domain {
+        // google.com/url?q=<url>
+        _ if is_google_domain(domain) && path == "/url" => query_param(url, &["q", "url"]),
+        // google.com/amp/s/<host>/<path>
+        _ if is_google_domain(domain) && path.starts_with("/amp/") => {
+            match path.trim_start_matches("/amp/").strip_prefix("s/") {
+                Some(secure) => Some(format!("https://{secure}")),
+                None => Some(format!("http://{}", path.trim_start_matches("/amp/"))),
+            }
+        }
+        // l.facebook.com/l.php?u=<url>
+        "l.facebook.com" | "lm.facebook.com" if path == "/l.php" => query_param(url, &["u"]),
+        // l.instagram.com/?u=<url>
+        "l.instagram.com" => query_param(url, &["u"]),
+        // youtube.com/redirect?q=<url>
+        "youtube.com" | "www.youtube.com" if path == "/redirect" => query_param(url, &["q"]),
+        // steamcommunity.com/linkfilter/?url=<url>
+        "steamcommunity.com" if path.trim_end_matches('/') == "/linkfilter" => {
+            query_param(url, &["url", "u"])
+        }
+        // out.reddit.com/<id>?url=<url>
+        "out.reddit.com" => query_param(url, &["url"]),
+        // <region>.safelinks.protection.outlook.com/?url=<url>
+        _ if domain.ends_with(".safelinks.protection.outlook.com") => query_param(url, &["url"]),
+        _ => None,
+    }?;
+
+    Url::parse(&inner)
+        .ok()
+        .filter(|inner| inner.scheme() == "https" || inner.scheme() == "http")
+}
+
+fn is_google_domain(domain: &str) -> bool {
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+    domain == "google.com" || domain.starts_with("google.")
+}
+
+fn query_param(url: &Url, names: &[&str]) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| names.contains(&key.as_ref()))
+        .map(|(_, value)| value.to_string())
+}
@@ -0,0 +1,149 @@
+// Specific logic for shopping sites, whose product links are full of referral and tracking junk
+
+use reqwest::{header::LOCATION, redirect, Client};
+use url::Url;
+
+use super::{
+    error::check_status, Link, PlatformLink, ResolutionError, Unsupported, RESOLUTION_TIMEOUT,
+};
+
+/// The TLDs of Amazon's regional storefronts.
+pub const AMAZON_TLDS: &[&str] = &[
+    "com", "ca", "com.mx", "com.br", "co.uk", "de", "fr", "it", "es", "nl", "se", "pl", "com.be",
+    "com.tr", "ie", "ae", "sa", "eg", "in", "co.jp", "sg", "com.au", "cn", "co.za",
+];
+
+/// The TLDs of eBay's regional sites.
+pub const EBAY_TLDS: &[&str] = &[
+    "com", "ca", "co.uk", "de", "fr", "it", "es", "nl", "be", "at", "ch", "ie", "pl", "com.au",
+    "com.hk", "com.my", "com.sg", "ph",
+];
+
+/// Returns the regional TLD of a store's domain, e.g. `co.uk` for `www.amazon.co.uk`,
+/// if it is one of the store's.
+pub fn store_tld<'a>(domain: &'a str, store: &str, tlds: &[&str]) -> Option<&'a str> {
+    let domain = domain
+        .strip_prefix("www.")
+        .or_else(|| domain.strip_prefix("smile."))
+        .or_else(|| domain.strip_prefix("m."))
+        .unwrap_or(domain);
+
+    domain
+        .strip_prefix(store)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .filter(|tld| tlds.contains(tld))
+}
+
+fn is_asin(asin: &str) -> bool {
+    asin.len() == 10 && asin.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+/// Parses `amazon.<tld>/(<slug>/)dp/<asin>`, `/gp/product/<asin>` and `/gp/aw/d/<asin>` links.
+pub fn parse_amazon_link(domain: &str, url: &Url) -> Result<PlatformLink, Unsupported> {
+    let tld = store_tld(domain, "amazon", AMAZON_TLDS).ok_or(Unsupported::Path)?;
+    let mut segments = url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter();
+
+    // the ASIN comes right after one of these markers, wherever they are in the path
+    let asin = segments
+        .by_ref()
+        .find(|s| matches!(*s, "dp" | "product" | "d" | "ASIN"))
+        .and_then(|_| segments.next())
+        .filter(|asin| is_asin(asin))
+        .ok_or(Unsupported::Path)?;
+
+    Ok(PlatformLink::AmazonProduct {
+        tld: tld.to_string(),
+        asin: asin.to_uppercase(),
+    })
+}
+
+/// Parses `aliexpress.com/item/<id>.html` links, on any regional subdomain.
+pub fn parse_aliexpress_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        [Some("item"), Some(item), None] => Ok(PlatformLink::AliExpressProduct {
+            item_id: item
+                .trim_end_matches(".html")
+                .parse()
+                .map_err(|_| Unsupported::Path)?,
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `ebay.<tld>/itm/(<slug>/)<id>` links.
+pub fn parse_ebay_link(domain: &str, url: &Url) -> Result<PlatformLink, Unsupported> {
+    let tld = store_tld(domain, "ebay", EBAY_TLDS).ok_or(Unsupported::Path)?;
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        [Some("itm"), Some(item_id), None, _] | [Some("itm"), Some(_), Some(item_id), None] => {
+            Ok(PlatformLink::EbayProduct {
+                tld: tld.to_string(),
+                item_id: item_id.parse().map_err(|_| Unsupported::Path)?,
+            })
+        }
+        _ => Err(Unsupported::Path),
+    }
+}
+
+pub async fn resolve_amazon_short_link(short_link: &str) -> Result<PlatformLink, ResolutionError> {
+    let client = Client::builder()
+        // stop as soon as we are redirected to a product page, no need to load it
+        .redirect(redirect::Policy::custom(|attempt| {
+            let is_product = attempt
+                .url()
+                .domain()
+                .is_some_and(|domain| parse_amazon_link(domain, attempt.url()).is_ok());
+
+            if is_product || attempt.previous().len() > 5 {
+                attempt.stop()
+            } else {
+                attempt.follow()
+            }
+        }))
+        .user_agent("curl/8.7.1")
+        .timeout(RESOLUTION_TIMEOUT)
+        .build()?;
+
+    let response = check_status(client.get(short_link).send().await?)?;
+    let real_link = response
+        .headers()
+        .get(LOCATION)
+        .and_then(|real_link| real_link.to_str().ok())
+        .and_then(|real_link| Url::parse(real_link).ok())
+        .ok_or(ResolutionError::Failed)?;
+
+    // filter to avoid infinite recursion
+    PlatformLink::try_from(real_link)
+        .ok()
+        .filter(|pl| matches!(pl, PlatformLink::AmazonProduct { .. }))
+        .ok_or(ResolutionError::Failed)
+}
+
+pub fn alternative_amazon_links(tld: &str, asin: &str) -> Vec<Link> {
+    vec![Link::Simple(format!("https://www.amazon.{tld}/dp/{asin}"))]
+}
+
+pub fn alternative_aliexpress_links(item_id: u64) -> Vec<Link> {
+    vec![Link::Simple(format!(
+        "https://www.aliexpress.com/item/{item_id}.html"
+    ))]
+}
+
+pub fn alternative_ebay_links(tld: &str, item_id: u64) -> Vec<Link> {
+    vec![Link::Simple(format!(
+        "https://www.ebay.{tld}/itm/{item_id}"
+    ))]
+}
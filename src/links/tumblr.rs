@@ -0,0 +1,46 @@
+// Specific logic for Tumblr, since posts can be linked from the blog's subdomain or tumblr.com
+
+use url::Url;
+
+use super::{Link, PlatformLink, Unsupported};
+
+/// Parses `<blog>.tumblr.com/post/<id>` links.
+pub fn parse_tumblr_subdomain_link(blog: &str, url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        // /post/<post_id>/<perhaps post slug>
+        [Some("post"), Some(post_id)] => Ok(PlatformLink::TumblrPost {
+            blog: blog.to_string(),
+            post_id: post_id.parse().map_err(|_| Unsupported::Path)?,
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `tumblr.com/<blog>/<id>` links.
+pub fn parse_tumblr_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        // /<blog>/<post_id>/<perhaps post slug>
+        [Some(blog), Some(post_id)] => Ok(PlatformLink::TumblrPost {
+            blog: blog.to_string(),
+            post_id: post_id.parse().map_err(|_| Unsupported::Path)?,
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+pub fn alternative_tumblr_links(blog: &str, post_id: u64) -> Vec<Link> {
+    vec![
+        Link::Embed(format!("https://www.tpmblr.com/{blog}/{post_id}")),
+        Link::Simple(format!("https://www.tumblr.com/{blog}/{post_id}")),
+    ]
+}
@@ -0,0 +1,231 @@
+// Specific logic for video platforms other than YouTube
+
+use std::borrow::Cow;
+
+use url::Url;
+
+use super::{Link, PlatformLink, Unsupported};
+
+/// Parses `clips.twitch.tv/<slug>` links.
+pub fn parse_twitch_clip_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        [Some(slug), None] => Ok(PlatformLink::TwitchClip {
+            slug: slug.to_string(),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `twitch.tv/<channel>/clip/<slug>` and `twitch.tv/videos/<id>` links.
+pub fn parse_twitch_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        // /<channel>/clip/<slug>
+        [Some(_channel), Some("clip"), Some(slug), None] => Ok(PlatformLink::TwitchClip {
+            slug: slug.to_string(),
+        }),
+        // /videos/<video_id>
+        [Some("videos"), Some(video_id), None, _] => {
+            let mut timestamp = None;
+            for (key, value) in url.query_pairs() {
+                if let Cow::Borrowed("t") = key {
+                    timestamp = parse_twitch_timestamp(&value);
+                }
+            }
+
+            Ok(PlatformLink::TwitchVod {
+                video_id: video_id.parse().map_err(|_| Unsupported::Path)?,
+                timestamp,
+            })
+        }
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `kick.com/<channel>/clips/<clip_id>` and `kick.com/<channel>?clip=<clip_id>` links.
+pub fn parse_kick_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next(), it.next()])
+        .unwrap_or([None; 4])
+    {
+        // /<channel>/clips/<clip_id>
+        [Some(channel), Some("clips"), Some(clip_id), None] => Ok(PlatformLink::KickClip {
+            channel: channel.to_string(),
+            clip_id: clip_id.to_string(),
+        }),
+        // /<channel>?clip=<clip_id>
+        [Some(channel), None, ..] => url
+            .query_pairs()
+            .find(|(key, _)| key == "clip")
+            .map(|(_, clip_id)| PlatformLink::KickClip {
+                channel: channel.to_string(),
+                clip_id: clip_id.to_string(),
+            })
+            .ok_or(Unsupported::Path),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `vimeo.com/<id>` and `player.vimeo.com/video/<id>` links,
+/// keeping the privacy hash of unlisted videos.
+pub fn parse_vimeo_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    let query_hash = url
+        .query_pairs()
+        .find(|(key, _)| key == "h")
+        .map(|(_, hash)| hash.to_string());
+
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        // /<video_id>/<perhaps hash>
+        [Some(video_id), hash, None] if video_id.bytes().all(|b| b.is_ascii_digit()) => {
+            Ok(PlatformLink::VimeoVideo {
+                video_id: video_id.parse().map_err(|_| Unsupported::Path)?,
+                hash: hash.map(|h| h.to_string()).or(query_hash),
+            })
+        }
+        // /video/<video_id> (player.vimeo.com)
+        [Some("video"), Some(video_id), None] => Ok(PlatformLink::VimeoVideo {
+            video_id: video_id.parse().map_err(|_| Unsupported::Path)?,
+            hash: query_hash,
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `dailymotion.com/video/<id>` links.
+pub fn parse_dailymotion_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next(), it.next()])
+        .unwrap_or([None; 3])
+    {
+        [Some("video"), Some(video_id), None] => Ok(PlatformLink::DailymotionVideo {
+            video_id: video_id.to_string(),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses `dai.ly/<id>` short links.
+pub fn parse_dailymotion_short_link(url: &Url) -> Result<PlatformLink, Unsupported> {
+    match url
+        .path_segments()
+        .map(|it| it.filter(|s| !s.is_empty()))
+        .map(|mut it| [it.next(), it.next()])
+        .unwrap_or([None; 2])
+    {
+        [Some(video_id), None] => Ok(PlatformLink::DailymotionVideo {
+            video_id: video_id.to_string(),
+        }),
+        _ => Err(Unsupported::Path),
+    }
+}
+
+/// Parses Twitch's `t` parameter, which is either plain seconds or `1h2m3s`.
+fn parse_twitch_timestamp(value: &str) -> Option<u32> {
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+
+    let mut total = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let multiplier = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total = number
+                    .parse::<u32>()
+                    .ok()?
+                    .checked_mul(multiplier)?
+                    .checked_add(total)?;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+
+    number.is_empty().then_some(total)
+}
+
+fn format_twitch_timestamp(timestamp: u32) -> String {
+    format!(
+        "{}h{}m{}s",
+        timestamp / 3600,
+        (timestamp % 3600) / 60,
+        timestamp % 60
+    )
+}
+
+pub fn alternative_twitch_clip_links(slug: &str) -> Vec<Link> {
+    vec![Link::Embed(format!("https://clips.twitch.tv/{slug}"))]
+}
+
+pub fn alternative_twitch_vod_links(video_id: u64, timestamp: Option<u32>) -> Vec<Link> {
+    if let Some(timestamp) = timestamp {
+        vec![Link::Simple(format!(
+            "https://www.twitch.tv/videos/{video_id}?t={}",
+            format_twitch_timestamp(timestamp)
+        ))]
+    } else {
+        vec![Link::Simple(format!(
+            "https://www.twitch.tv/videos/{video_id}"
+        ))]
+    }
+}
+
+pub fn alternative_kick_clip_links(channel: &str, clip_id: &str) -> Vec<Link> {
+    vec![Link::Simple(format!(
+        "https://kick.com/{channel}/clips/{clip_id}"
+    ))]
+}
+
+pub fn alternative_vimeo_links(video_id: u64, hash: Option<&str>) -> Vec<Link> {
+    if let Some(hash) = hash {
+        vec![Link::Embed(format!("https://vimeo.com/{video_id}/{hash}"))]
+    } else {
+        vec![Link::Embed(format!("https://vimeo.com/{video_id}"))]
+    }
+}
+
+pub fn alternative_dailymotion_links(video_id: &str) -> Vec<Link> {
+    vec![
+        Link::Embed(format!("https://www.dailymotion.com/video/{video_id}")),
+        Link::Simple(format!("https://dai.ly/{video_id}")),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_twitch_timestamp;
+
+    #[test]
+    fn test_parse_twitch_timestamp() {
+        assert_eq!(Some(90), parse_twitch_timestamp("90"));
+        assert_eq!(Some(3723), parse_twitch_timestamp("1h2m3s"));
+        assert_eq!(None, parse_twitch_timestamp("1h2"));
+        // too long to be a timestamp, instead of overflowing
+        assert_eq!(None, parse_twitch_timestamp("9999999h"));
+        assert_eq!(None, parse_twitch_timestamp("1193046h28m16s"));
+    }
+}
@@ -2,20 +2,52 @@ use std::env;
 
 use serenity::async_trait;
 use serenity::model::application::Interaction;
+use serenity::model::channel::{Message, Reaction};
 use serenity::model::gateway::Ready;
+use serenity::model::id::{ChannelId, GuildId, MessageId};
 use serenity::prelude::*;
 
+mod config;
+mod i18n;
+use i18n::Localize;
+mod limits;
 mod links;
+mod reactions;
+mod replies;
+use replies::{delete_replies, replies};
+mod policy;
+use policy::Activity;
+mod repost;
+use repost::{alert_moderators, Reposter};
+mod safety;
+mod settings;
 
 mod interactions;
-use interactions::{MsgCommand, RRCommandInteraction, SlashCommand};
+use interactions::{
+    handle_component, language, respond_error, respond_privately, AutoCommand, MsgCommand,
+    PlatformsCommand, PolicyCommand, PrefsCommand, RRCommandInteraction, SlashCommand,
+};
 
 #[tokio::main]
 async fn main() {
     let token =
         env::var("DISCORD_TOKEN").expect("Please set the environment variable DISCORD_TOKEN");
+    // fail now rather than in the first event that needs the settings
+    settings::settings();
 
-    let mut client = Client::builder(token, GatewayIntents::empty())
+    // reading the messages sent and reacted to needs their content, a privileged intent that
+    // has to be enabled for the bot, so it is only asked for by the features that need it
+    let config = config::config();
+    let mut intents = GatewayIntents::empty();
+    if config.reaction_emoji.is_some() {
+        intents |= GatewayIntents::GUILD_MESSAGE_REACTIONS
+            | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+            | GatewayIntents::MESSAGE_CONTENT;
+    }
+    if config.read_messages {
+        intents |= GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    }
+    let mut client = Client::builder(token, intents)
         .event_handler(Handler::new())
         .await
         .expect("Error creating client");
@@ -27,12 +59,21 @@ async fn main() {
 
 struct Handler {
     command_interactions: Vec<Box<dyn RRCommandInteraction + Sync + Send>>,
+    reposter: Reposter,
 }
 
 impl Handler {
     fn new() -> Self {
         Self {
-            command_interactions: vec![Box::new(MsgCommand), Box::new(SlashCommand)],
+            command_interactions: vec![
+                Box::new(MsgCommand),
+                Box::new(SlashCommand),
+                Box::new(AutoCommand),
+                Box::new(PrefsCommand),
+                Box::new(PolicyCommand),
+                Box::new(PlatformsCommand),
+            ],
+            reposter: Reposter::new(),
         }
     }
 }
@@ -40,15 +81,104 @@ impl Handler {
 #[async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            for interaction in &self.command_interactions {
-                if interaction.can_handle(&command) {
-                    if let Err(e) = interaction.handle(&ctx, &command).await {
-                        println!("failed to handle {}: {e}", interaction.name());
+        match interaction {
+            Interaction::Command(command) => {
+                for interaction in &self.command_interactions {
+                    if interaction.can_handle(&command) {
+                        let roles = command
+                            .member
+                            .as_ref()
+                            .map_or(&[][..], |member| &member.roles);
+                        let denied = policy::check(
+                            command.guild_id,
+                            command.channel_id,
+                            roles,
+                            Activity::Command,
+                        )
+                        .err()
+                        .filter(|_| !interaction.bypasses_policy());
+                        let result = if let Some(denied) = denied {
+                            respond_error(&ctx, &command, denied).await
+                        } else if let Some(Err(limited)) =
+                            interaction.is_rate_limited().then(|| {
+                                limits::acquire(
+                                    Some(command.user.id),
+                                    command.channel_id,
+                                    command.guild_id,
+                                )
+                            })
+                        {
+                            let limited = limited.localize(language(&command));
+                            respond_privately(&ctx, &command, format!(":hourglass: {limited}"))
+                                .await
+                        } else {
+                            interaction.handle(&ctx, &command).await
+                        };
+                        if let Err(e) = result {
+                            println!("failed to handle {}: {e}", interaction.name());
+                        }
+                        break;
                     }
-                    break;
                 }
             }
+            Interaction::Component(component) => {
+                if let Err(e) = handle_component(&ctx, &component).await {
+                    println!("failed to handle component: {e}");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        // moderators are alerted about dangerous links wherever they are sent
+        if let Err(e) = alert_moderators(&ctx, &msg).await {
+            println!("failed to alert moderators: {e}");
+        }
+
+        let roles = msg.member.as_ref().map_or(&[][..], |member| &member.roles);
+        if policy::check(msg.guild_id, msg.channel_id, roles, Activity::AutoMode).is_err() {
+            return;
+        }
+        if let Err(e) = self.reposter.message(&ctx, &msg).await {
+            println!("failed to repost message: {e}");
+        }
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        _channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        // deleting any of the replies deletes the others, and restores the original embeds
+        if let Some(mut reply) = replies().take(deleted_message_id, |_| true) {
+            reply.replies.retain(|&id| id != deleted_message_id);
+            if let Err(e) = delete_replies(&ctx, reply).await {
+                println!("failed to delete replies: {e}");
+            }
+        }
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let roles = reaction
+            .member
+            .as_ref()
+            .map_or(&[][..], |member| &member.roles);
+        let denied = policy::check(
+            reaction.guild_id,
+            reaction.channel_id,
+            roles,
+            Activity::Reaction,
+        )
+        .is_err();
+        // replies can still be deleted where the bot was disabled after replying
+        if reactions::is_trigger(&reaction) && denied {
+            return;
+        }
+        if let Err(e) = reactions::reaction_add(&ctx, &reaction).await {
+            println!("failed to handle reaction: {e}");
         }
     }
 
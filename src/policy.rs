@@ -0,0 +1,211 @@
+// Where and for whom the bot acts in each guild, as configured by its moderators
+
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId, RoleId};
+
+use crate::{
+    i18n::{Language, Localize},
+    links::Platform,
+    settings::settings,
+};
+
+/// What the bot is about to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    Command,
+    Reaction,
+    AutoMode,
+}
+
+/// Why the bot may not act.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Denied {
+    DisabledChannel,
+    NotAutoModeChannel,
+    ExemptRole,
+}
+
+impl Localize for Denied {
+    fn localize(&self, language: Language) -> String {
+        language.tr(match self {
+            Denied::DisabledChannel => "denied.disabled_channel",
+            Denied::NotAutoModeChannel => "denied.not_auto_mode_channel",
+            Denied::ExemptRole => "denied.exempt_role",
+        })
+    }
+}
+
+impl Display for Denied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// Channels where the bot does nothing
+    pub disabled_channels: Vec<ChannelId>,
+    /// Channels where auto mode is used, or all of them if there are none
+    pub auto_mode_channels: Vec<ChannelId>,
+    /// Roles whose members the bot leaves alone
+    pub exempt_roles: Vec<RoleId>,
+    /// Platforms whose links the bot leaves alone
+    pub disabled_platforms: Vec<Platform>,
+    /// Platforms enabled or disabled in some channels, regardless of the rest of the guild
+    pub channel_platforms: HashMap<ChannelId, HashMap<Platform, bool>>,
+}
+
+impl Policy {
+    /// Whether the bot may act in the channel, for a member with the given roles.
+    pub fn check(
+        &self,
+        channel: ChannelId,
+        roles: &[RoleId],
+        activity: Activity,
+    ) -> Result<(), Denied> {
+        if self.disabled_channels.contains(&channel) {
+            Err(Denied::DisabledChannel)
+        } else if activity == Activity::AutoMode
+            && !self.auto_mode_channels.is_empty()
+            && !self.auto_mode_channels.contains(&channel)
+        {
+            Err(Denied::NotAutoModeChannel)
+        } else if roles.iter().any(|role| self.exempt_roles.contains(role)) {
+            Err(Denied::ExemptRole)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether the bot handles links to the platform in the channel.
+    pub fn enables(&self, channel: ChannelId, platform: Platform) -> bool {
+        self.channel_platforms
+            .get(&channel)
+            .and_then(|platforms| platforms.get(&platform).copied())
+            .unwrap_or(!self.disabled_platforms.contains(&platform))
+    }
+
+    /// Enables or disables the platform in the whole guild, or only in the given channel.
+    pub fn switch_platform(
+        &mut self,
+        platform: Platform,
+        enabled: bool,
+        channel: Option<ChannelId>,
+    ) {
+        match channel {
+            Some(channel) => {
+                let guild_enabled = !self.disabled_platforms.contains(&platform);
+                let platforms = self.channel_platforms.entry(channel).or_default();
+                // channels only remember how they differ from the rest of the guild
+                if enabled == guild_enabled {
+                    platforms.remove(&platform);
+                } else {
+                    platforms.insert(platform, enabled);
+                }
+                if platforms.is_empty() {
+                    self.channel_platforms.remove(&channel);
+                }
+            }
+            None => {
+                self.disabled_platforms.retain(|other| *other != platform);
+                if !enabled {
+                    self.disabled_platforms.push(platform);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the bot may act, according to the policy of the guild. Outside of guilds it always may.
+pub fn check(
+    guild_id: Option<GuildId>,
+    channel: ChannelId,
+    roles: &[RoleId],
+    activity: Activity,
+) -> Result<(), Denied> {
+    match guild_id {
+        Some(guild_id) => settings()
+            .guild(guild_id)
+            .policy
+            .check(channel, roles, activity),
+        None => Ok(()),
+    }
+}
+
+/// The platforms whose links the bot leaves alone in the channel. Outside of guilds there are none.
+pub fn disabled_platforms(guild_id: Option<GuildId>, channel: ChannelId) -> Vec<Platform> {
+    let Some(guild_id) = guild_id else {
+        return vec![];
+    };
+    let policy = settings().guild(guild_id).policy;
+    Platform::ALL
+        .into_iter()
+        .filter(|platform| !policy.enables(channel, *platform))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::{ChannelId, RoleId};
+
+    use crate::links::Platform;
+
+    use super::{Activity, Denied, Policy};
+
+    #[test]
+    fn test_check_policy() {
+        let policy = Policy {
+            disabled_channels: vec![ChannelId::new(1)],
+            auto_mode_channels: vec![ChannelId::new(2)],
+            exempt_roles: vec![RoleId::new(10)],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Err(Denied::DisabledChannel),
+            policy.check(ChannelId::new(1), &[], Activity::Command)
+        );
+        assert_eq!(
+            Ok(()),
+            policy.check(ChannelId::new(3), &[], Activity::Command)
+        );
+        assert_eq!(
+            Err(Denied::NotAutoModeChannel),
+            policy.check(ChannelId::new(3), &[], Activity::AutoMode)
+        );
+        assert_eq!(
+            Ok(()),
+            policy.check(ChannelId::new(2), &[], Activity::AutoMode)
+        );
+        assert_eq!(
+            Err(Denied::ExemptRole),
+            policy.check(
+                ChannelId::new(2),
+                &[RoleId::new(11), RoleId::new(10)],
+                Activity::Reaction
+            )
+        );
+    }
+
+    #[test]
+    fn test_switch_platforms() {
+        let mut policy = Policy::default();
+        let (media, serious) = (ChannelId::new(1), ChannelId::new(2));
+
+        policy.switch_platform(Platform::Youtube, false, None);
+        policy.switch_platform(Platform::Youtube, true, Some(media));
+        policy.switch_platform(Platform::Twitter, false, Some(serious));
+        assert!(policy.enables(media, Platform::Youtube));
+        assert!(!policy.enables(serious, Platform::Youtube));
+        assert!(policy.enables(media, Platform::Twitter));
+        assert!(!policy.enables(serious, Platform::Twitter));
+
+        // enabling it everywhere makes the channel no longer differ
+        policy.switch_platform(Platform::Youtube, true, None);
+        policy.switch_platform(Platform::Youtube, true, Some(media));
+        assert!(!policy.channel_platforms.contains_key(&media));
+    }
+}
@@ -0,0 +1,113 @@
+// Reaction-triggered conversion: reacting to a message with the configured emoji makes the bot
+// reply with alternative links, and the original poster can react to the reply to delete it
+
+use serenity::all::{Context, CreateAllowedMentions, CreateMessage, Reaction};
+
+use crate::{
+    config::config,
+    interactions::{components, track_response, MessageLinks},
+    limits,
+    policy::disabled_platforms,
+    replies::{delete_replies, replies, suppress_embeds, Reply},
+    settings::settings,
+};
+
+/// Reacting with this to a reply of the bot deletes it, when done by the original poster.
+const DELETE_EMOJI: &str = "❌";
+
+/// Whether the reaction asks the bot to reply with alternative links.
+pub fn is_trigger(reaction: &Reaction) -> bool {
+    config()
+        .reaction_emoji
+        .as_deref()
+        .is_some_and(|emoji| reaction.emoji.unicode_eq(emoji))
+}
+
+pub async fn reaction_add(ctx: &Context, reaction: &Reaction) -> serenity::Result<()> {
+    if reaction
+        .member
+        .as_ref()
+        .is_some_and(|member| member.user.bot)
+    {
+        return Ok(());
+    }
+
+    if reaction.emoji.unicode_eq(DELETE_EMOJI) {
+        let reply = replies().take(reaction.message_id, |reply| {
+            Some(reply.author) == reaction.user_id
+        });
+        match reply {
+            Some(reply) => delete_replies(ctx, reply).await,
+            None => Ok(()),
+        }
+    } else if is_trigger(reaction) {
+        reply(ctx, reaction).await
+    } else {
+        Ok(())
+    }
+}
+
+async fn reply(ctx: &Context, reaction: &Reaction) -> serenity::Result<()> {
+    // claimed before anything is awaited, so that quick reactions do not reply twice
+    if !replies().try_claim(reaction.message_id) {
+        return Ok(());
+    }
+    let result = reply_to_message(ctx, reaction).await;
+    // tracking the replies took the claim over, if there were any
+    replies().release(reaction.message_id);
+    result
+}
+
+async fn reply_to_message(ctx: &Context, reaction: &Reaction) -> serenity::Result<()> {
+    let msg = reaction.message(&ctx.http).await?;
+    let Some(invoker) = reaction.user_id else {
+        return Ok(());
+    };
+    let links = MessageLinks::find(
+        &[&msg],
+        &disabled_platforms(reaction.guild_id, reaction.channel_id),
+    );
+    // reactions are not always meant for the bot, so there is nothing to complain about, and
+    // they only count towards the rate limits when there is something to reply with. Reactions
+    // over the rate limits are ignored, since there is no way to tell only who reacted.
+    if links.is_empty()
+        || limits::acquire(reaction.user_id, reaction.channel_id, reaction.guild_id).is_err()
+    {
+        return Ok(());
+    }
+    let Ok(response) = links.response().await else {
+        return Ok(());
+    };
+    let response = response.prefer(&settings().user(invoker).frontends);
+
+    let pages = response.pages();
+    let page_count = pages.len();
+    let components = components(&response, page_count == 1, invoker);
+    let mut reply_ids = vec![];
+    for (i, page) in pages.into_iter().enumerate() {
+        let mut reply = CreateMessage::new()
+            .content(page)
+            .allowed_mentions(CreateAllowedMentions::new().replied_user(false));
+        if i == 0 {
+            reply = reply.reference_message(&msg);
+        }
+        if i == page_count - 1 {
+            reply = reply.components(components.clone());
+        }
+        reply_ids.push(msg.channel_id.send_message(&ctx.http, reply).await?.id);
+    }
+    if let Some(&last_page) = reply_ids.last().filter(|_| !components.is_empty()) {
+        track_response(last_page, response.clone(), invoker);
+    }
+
+    replies().track(Reply {
+        source: Some(msg.id),
+        channel: msg.channel_id,
+        interaction_token: None,
+        author: msg.author.id,
+        replies: reply_ids,
+        suppressed: response.has_embeds() && suppress_embeds(ctx, &msg).await,
+    });
+
+    Ok(())
+}
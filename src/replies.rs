@@ -0,0 +1,173 @@
+// Bookkeeping of which replies of the bot belong to which message, so that they can be deleted
+// together, and the embeds of the message restored if they were suppressed
+
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Mutex, OnceLock},
+};
+
+use serenity::all::{ChannelId, Context, EditMessage, Message, MessageId, UserId};
+
+use crate::settings::settings;
+
+/// Only the latest replies are remembered, older ones are left as they are.
+const MAX_TRACKED_REPLIES: usize = 1000;
+
+pub struct Reply {
+    /// The message replied to, if the replies are to a message and not to a command
+    pub source: Option<MessageId>,
+    pub channel: ChannelId,
+    /// Token of the command interaction that was responded to, through which the responses can be
+    /// deleted even where the bot was not added to the server
+    pub interaction_token: Option<String>,
+    /// Author of the source message, or whoever used the command without one
+    pub author: UserId,
+    pub replies: Vec<MessageId>,
+    /// Whether the embeds of the source message were suppressed
+    pub suppressed: bool,
+}
+
+#[derive(Default)]
+pub struct Replies {
+    tracked: Mutex<VecDeque<Reply>>,
+    /// Source messages that are being replied to, but whose replies are not tracked yet
+    claimed: Mutex<HashSet<MessageId>>,
+}
+
+impl Replies {
+    pub fn track(&self, reply: Reply) {
+        let mut tracked = self.tracked.lock().unwrap();
+        if tracked.len() == MAX_TRACKED_REPLIES {
+            tracked.pop_front();
+        }
+        if let Some(source) = reply.source {
+            self.claimed.lock().unwrap().remove(&source);
+        }
+        tracked.push_back(reply);
+    }
+
+    /// Claims the message to reply to it, unless it was already replied to or claimed,
+    /// so that it is only replied to once even when asked to more than once at the same time.
+    pub fn try_claim(&self, source: MessageId) -> bool {
+        let tracked = self.tracked.lock().unwrap();
+        !tracked.iter().any(|reply| reply.source == Some(source))
+            && self.claimed.lock().unwrap().insert(source)
+    }
+
+    /// Gives up the claim to the message, if its replies were not tracked.
+    pub fn release(&self, source: MessageId) {
+        self.claimed.lock().unwrap().remove(&source);
+    }
+
+    /// Stops tracking the replies that the given message is part of, if allowed to.
+    pub fn take(&self, reply: MessageId, allowed: impl FnOnce(&Reply) -> bool) -> Option<Reply> {
+        let mut tracked = self.tracked.lock().unwrap();
+        let i = tracked
+            .iter()
+            .position(|tracked| tracked.replies.contains(&reply))?;
+        if !allowed(&tracked[i]) {
+            return None;
+        }
+        tracked.remove(i)
+    }
+}
+
+pub fn replies() -> &'static Replies {
+    static REPLIES: OnceLock<Replies> = OnceLock::new();
+    REPLIES.get_or_init(Replies::default)
+}
+
+/// Suppresses the embeds of a message that was replied to with better ones, if the guild wants
+/// that. Returns whether they were suppressed, which fails without the Manage Messages permission.
+pub async fn suppress_embeds(ctx: &Context, msg: &Message) -> bool {
+    let wanted = msg
+        .guild_id
+        .is_some_and(|guild_id| settings().guild(guild_id).suppress_embeds);
+    if !wanted || msg.embeds.is_empty() {
+        return false;
+    }
+
+    let edit = EditMessage::new().suppress_embeds(true);
+    msg.channel_id
+        .edit_message(&ctx.http, msg.id, edit)
+        .await
+        .is_ok()
+}
+
+/// Deletes the replies that are left, and restores the embeds of the source message.
+pub async fn delete_replies(ctx: &Context, reply: Reply) -> serenity::Result<()> {
+    for id in reply.replies {
+        // interaction tokens expire after 15 minutes, when only the bot's own messages can be
+        // deleted, from servers where it was added
+        let deleted = match &reply.interaction_token {
+            Some(token) => match ctx.http.delete_followup_message(token, id).await {
+                Ok(()) => Ok(()),
+                Err(_) => reply.channel.delete_message(&ctx.http, id).await,
+            },
+            None => reply.channel.delete_message(&ctx.http, id).await,
+        };
+        if let Err(e) = deleted {
+            println!("failed to delete reply {id}: {e}");
+        }
+    }
+    if let Some(source) = reply.source.filter(|_| reply.suppressed) {
+        let edit = EditMessage::new().suppress_embeds(false);
+        reply.channel.edit_message(&ctx.http, source, edit).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::{ChannelId, MessageId, UserId};
+
+    use super::{Replies, Reply};
+
+    #[test]
+    fn test_try_claim() {
+        let replies = Replies::default();
+        let source = MessageId::new(1);
+
+        assert!(replies.try_claim(source));
+        assert!(!replies.try_claim(source));
+        replies.release(source);
+        assert!(replies.try_claim(source));
+
+        replies.track(Reply {
+            source: Some(source),
+            channel: ChannelId::new(10),
+            interaction_token: None,
+            author: UserId::new(100),
+            replies: vec![MessageId::new(2)],
+            suppressed: false,
+        });
+        // releasing after tracking does not make the message claimable again
+        replies.release(source);
+        assert!(!replies.try_claim(source));
+    }
+
+    #[test]
+    fn test_take_every_page() {
+        let replies = Replies::default();
+        let pages = [3, 4, 5].map(MessageId::new).to_vec();
+        replies.track(Reply {
+            source: None,
+            channel: ChannelId::new(10),
+            interaction_token: None,
+            author: UserId::new(100),
+            replies: pages.clone(),
+            suppressed: false,
+        });
+
+        // only whoever may delete them can take them
+        assert!(replies.take(MessageId::new(5), |_| false).is_none());
+        // the last page has the delete button, and the others go along with it
+        assert_eq!(
+            Some(pages),
+            replies
+                .take(MessageId::new(5), |_| true)
+                .map(|reply| reply.replies)
+        );
+        assert!(replies.take(MessageId::new(3), |_| true).is_none());
+    }
+}
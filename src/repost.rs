@@ -0,0 +1,281 @@
+// Auto mode: messages with supported links are reposted through a webhook with their links fixed,
+// as if the author had sent them that way
+
+use std::{collections::HashMap, sync::Mutex};
+
+use serenity::all::{
+    Channel, ChannelId, Context, CreateAllowedMentions, CreateMessage, CreateWebhook, EditMessage,
+    ExecuteWebhook, GuildId, HttpError, Mentionable, Message, Webhook,
+};
+use url::Url;
+
+use crate::{
+    config::config,
+    limits,
+    links::{find_platform_links, join_bounded, Link, Platform},
+    policy::disabled_platforms,
+    safety::check_message,
+    settings::{settings, AutoMode},
+};
+
+/// Name of the webhooks created by the bot, also used to find them again.
+const WEBHOOK_NAME: &str = "Alt URLs";
+
+/// Discord's JSON error code for webhooks that do not exist (anymore).
+const UNKNOWN_WEBHOOK: isize = 10015;
+
+fn is_unknown_webhook(error: &serenity::Error) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.error.code == UNKNOWN_WEBHOOK
+    )
+}
+
+/// Replaces each supported link in the message by its primary embed alternative,
+/// or returns `None` if no link is better off replaced.
+pub async fn fix_links(message: &str, disabled: &[Platform]) -> Option<String> {
+    let replacements = join_bounded(
+        find_platform_links(message, disabled)
+            .into_iter()
+            // whoever wrote suppressed links did not want them embedded
+            .filter(|found| !found.suppressed && found.link.is_ok())
+            .take(config().max_links_per_invocation)
+            .map(|found| async move {
+                let embed = found
+                    .link
+                    .ok()?
+                    .alternative_links()
+                    .await
+                    .ok()?
+                    .into_iter()
+                    .find_map(|link| match link {
+                        Link::Embed(url) => Some(url),
+                        _ => None,
+                    })?;
+
+                // links that already embed fine, like Vimeo videos, are left alone
+                let host = |url: &str| Url::parse(url).ok()?.host_str().map(str::to_owned);
+                (host(&embed) != host(&found.url)).then_some((found.range, embed))
+            }),
+    )
+    .await;
+
+    let mut replacements = replacements.into_iter().flatten().collect::<Vec<_>>();
+    if replacements.is_empty() {
+        return None;
+    }
+
+    // replaced from the end, so that the ranges before stay valid
+    replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut fixed = message.to_owned();
+    for (range, embed) in replacements {
+        fixed.replace_range(range, &embed);
+    }
+    Some(fixed)
+}
+
+/// Alerts the moderators of the guild about dangerous links in the message, if they want that.
+pub async fn alert_moderators(ctx: &Context, msg: &Message) -> serenity::Result<()> {
+    let Some(alert_channel) = msg
+        .guild_id
+        .and_then(|guild_id| settings().guild(guild_id).alert_channel)
+    else {
+        return Ok(());
+    };
+    if msg.author.bot || msg.webhook_id.is_some() {
+        return Ok(());
+    }
+    let warnings = check_message(&msg.content);
+    if warnings.is_empty() {
+        return Ok(());
+    }
+
+    let alert = warnings
+        .iter()
+        .map(|warning| format!(":warning: {warning}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let alert = CreateMessage::new()
+        .content(format!(
+            "{} sent a suspicious message in {}: {}\n{alert}",
+            msg.author.mention(),
+            msg.channel_id.mention(),
+            msg.link(),
+        ))
+        .allowed_mentions(CreateAllowedMentions::new());
+    alert_channel.send_message(&ctx.http, alert).await?;
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct Reposter {
+    webhooks: Mutex<HashMap<ChannelId, Webhook>>,
+}
+
+impl Reposter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn message(&self, ctx: &Context, msg: &Message) -> serenity::Result<()> {
+        let Some(guild_id) = msg.guild_id else {
+            return Ok(());
+        };
+        // also keeps the bot from reposting its own reposts
+        if msg.author.bot || msg.webhook_id.is_some() {
+            return Ok(());
+        }
+
+        let guild_settings = settings().guild(guild_id);
+        // dangerous links are not reposted, to not make them look trustworthy
+        if !check_message(&msg.content).is_empty() {
+            return Ok(());
+        }
+
+        // reposts can not keep attachments, stickers or replies
+        if guild_settings.auto_mode == AutoMode::Off
+            || settings().user(msg.author.id).opt_out
+            || !msg.attachments.is_empty()
+            || !msg.sticker_items.is_empty()
+            || msg.message_reference.is_some()
+        {
+            return Ok(());
+        }
+        // only messages with links to fix count towards the rate limits, and those over them
+        // are left alone, since there is nobody to tell
+        let disabled = disabled_platforms(msg.guild_id, msg.channel_id);
+        if !find_platform_links(&msg.content, &disabled)
+            .iter()
+            .any(|found| found.link.is_ok())
+            || limits::acquire(Some(msg.author.id), msg.channel_id, msg.guild_id).is_err()
+        {
+            return Ok(());
+        }
+        let Some(fixed) = fix_links(&msg.content, &disabled).await else {
+            return Ok(());
+        };
+
+        self.repost(ctx, guild_id, msg, fixed).await?;
+        match guild_settings.auto_mode {
+            AutoMode::Delete => msg.delete(&ctx.http).await?,
+            AutoMode::Suppress => {
+                let edit = EditMessage::new().suppress_embeds(true);
+                msg.channel_id.edit_message(&ctx.http, msg.id, edit).await?;
+            }
+            AutoMode::Off => {}
+        }
+
+        Ok(())
+    }
+
+    async fn repost(
+        &self,
+        ctx: &Context,
+        guild_id: GuildId,
+        msg: &Message,
+        content: String,
+    ) -> serenity::Result<()> {
+        // webhooks belong to the channel that threads are in
+        let (channel_id, thread_id) = match msg.channel_id.to_channel(&ctx.http).await? {
+            Channel::Guild(channel) if channel.thread_metadata.is_some() => {
+                (channel.parent_id.unwrap_or(channel.id), Some(channel.id))
+            }
+            _ => (msg.channel_id, None),
+        };
+        let name = msg
+            .member
+            .as_ref()
+            .and_then(|member| member.nick.clone())
+            .unwrap_or_else(|| msg.author.display_name().to_owned());
+        let avatar = msg
+            .member
+            .as_ref()
+            .and_then(|member| member.avatar.as_ref())
+            .map(|avatar| {
+                format!(
+                    "https://cdn.discordapp.com/guilds/{guild_id}/users/{}/avatars/{avatar}.png",
+                    msg.author.id
+                )
+            })
+            .unwrap_or_else(|| msg.author.face());
+
+        let mut repost = ExecuteWebhook::new()
+            .content(content)
+            .username(name)
+            .avatar_url(avatar)
+            .allowed_mentions(CreateAllowedMentions::new());
+        if let Some(thread_id) = thread_id {
+            repost = repost.in_thread(thread_id);
+        }
+        let webhook = self.webhook(ctx, channel_id).await?;
+        match webhook.execute(&ctx.http, false, repost.clone()).await {
+            // the cached webhook was deleted, so a new one is needed
+            Err(e) if is_unknown_webhook(&e) => {
+                self.webhooks.lock().unwrap().remove(&channel_id);
+                let webhook = self.webhook(ctx, channel_id).await?;
+                webhook.execute(&ctx.http, false, repost).await?;
+            }
+            result => {
+                result?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The webhook of the bot in the channel, which is created if there is none yet.
+    async fn webhook(&self, ctx: &Context, channel_id: ChannelId) -> serenity::Result<Webhook> {
+        if let Some(webhook) = self.webhooks.lock().unwrap().get(&channel_id) {
+            return Ok(webhook.clone());
+        }
+
+        // only the webhooks created by the bot come with their token
+        let existing = channel_id
+            .webhooks(&ctx.http)
+            .await?
+            .into_iter()
+            .find(|webhook| {
+                webhook.name.as_deref() == Some(WEBHOOK_NAME) && webhook.token.is_some()
+            });
+        let webhook = match existing {
+            Some(webhook) => webhook,
+            None => {
+                channel_id
+                    .create_webhook(&ctx.http, CreateWebhook::new(WEBHOOK_NAME))
+                    .await?
+            }
+        };
+
+        self.webhooks
+            .lock()
+            .unwrap()
+            .insert(channel_id, webhook.clone());
+        Ok(webhook)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fix_links;
+
+    #[tokio::test]
+    async fn test_fix_links() {
+        let message =
+            "look https://x.com/johndoe/status/123?s=20 and <https://x.com/janedoe/status/456> \
+            ||https://www.instagram.com/reel/AAAAAAAAAAA/|| https://vimeo.com/123";
+
+        assert_eq!(
+            Some(
+                "look https://fxtwitter.com/johndoe/status/123 and <https://x.com/janedoe/status/456> \
+                ||https://www.ddinstagram.com/reel/AAAAAAAAAAA/|| https://vimeo.com/123"
+                    .to_owned()
+            ),
+            fix_links(message, &[]).await
+        );
+        assert_eq!(
+            None,
+            fix_links("nothing to fix in https://vimeo.com/123", &[]).await
+        );
+    }
+}
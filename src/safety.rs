@@ -0,0 +1,297 @@
+// Safety checks for the links in a message: lookalike domains, blocklisted domains
+// and masked links whose text points somewhere else than their target
+
+use std::{collections::HashSet, fmt::Display, fs, sync::OnceLock};
+
+use linkify::{LinkFinder, LinkKind};
+use url::Url;
+
+use crate::{
+    config::config,
+    i18n::{Language, Localize},
+};
+
+/// Domains that are commonly impersonated by phishing links.
+const KNOWN_DOMAINS: &[&str] = &[
+    "discord.com",
+    "discord.gg",
+    "discord.gift",
+    "discordapp.com",
+    "discord.media",
+    "steamcommunity.com",
+    "steampowered.com",
+    "twitter.com",
+    "x.com",
+    "instagram.com",
+    "facebook.com",
+    "youtube.com",
+    "reddit.com",
+    "twitch.tv",
+    "github.com",
+    "google.com",
+    "paypal.com",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    Lookalike { domain: String, imitates: String },
+    Blocklisted { domain: String },
+    MaskedLinkMismatch { text: String, target: String },
+}
+
+impl Localize for Warning {
+    fn localize(&self, language: Language) -> String {
+        match self {
+            Warning::Lookalike { domain, imitates } => language.tr_with(
+                "warning.lookalike",
+                &[("domain", domain), ("imitates", imitates)],
+            ),
+            Warning::Blocklisted { domain } => {
+                language.tr_with("warning.blocklisted", &[("domain", domain)])
+            }
+            Warning::MaskedLinkMismatch { text, target } => language.tr_with(
+                "warning.masked_link_mismatch",
+                &[("text", text), ("target", target)],
+            ),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.localize(Language::English))
+    }
+}
+
+/// Checks every link in the given message, returning one warning per problem found.
+pub fn check_message(message: &str) -> Vec<Warning> {
+    let mut warnings = LinkFinder::new()
+        .kinds(&[LinkKind::Url])
+        .links(message)
+        .filter_map(|link| Url::parse(link.as_str()).ok())
+        .filter_map(|url| url.domain().map(|domain| domain.to_string()))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .flat_map(|domain| check_domain(&domain))
+        .collect::<Vec<_>>();
+
+    warnings.extend(check_masked_links(message));
+    warnings
+}
+
+fn check_domain(domain: &str) -> Vec<Warning> {
+    let mut warnings = vec![];
+
+    if is_blocklisted(domain) {
+        warnings.push(Warning::Blocklisted {
+            domain: domain.to_string(),
+        });
+    }
+    if let Some(imitates) = imitated_domain(domain) {
+        warnings.push(Warning::Lookalike {
+            domain: domain.to_string(),
+            imitates: imitates.to_string(),
+        });
+    }
+
+    warnings
+}
+
+fn is_subdomain_of(domain: &str, parent: &str) -> bool {
+    domain == parent
+        || domain
+            .strip_suffix(parent)
+            .is_some_and(|subdomain| subdomain.ends_with('.'))
+}
+
+fn blocklist() -> &'static Vec<String> {
+    static BLOCKLIST: OnceLock<Vec<String>> = OnceLock::new();
+    BLOCKLIST.get_or_init(|| {
+        let Some(path) = &config().blocklist_path else {
+            return vec![];
+        };
+        match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .collect(),
+            Err(e) => {
+                println!("failed to read blocklist {path}: {e}");
+                vec![]
+            }
+        }
+    })
+}
+
+fn is_blocklisted(domain: &str) -> bool {
+    blocklist()
+        .iter()
+        .any(|blocked| is_subdomain_of(domain, blocked))
+}
+
+/// Maps characters that are commonly confused with each other to the same representation,
+/// so that lookalike domains end up with the same skeleton as the domain they imitate.
+fn skeleton(domain: &str) -> String {
+    let unconfused = domain
+        .chars()
+        .map(|c| match c {
+            // cyrillic and greek homoglyphs
+            'а' | 'α' => 'a',
+            'с' | 'ϲ' => 'c',
+            'ԁ' => 'd',
+            'е' | 'ε' => 'e',
+            'ɡ' => 'g',
+            'һ' => 'h',
+            'і' | 'ı' | 'ι' => 'i',
+            'ј' => 'j',
+            'ӏ' => 'l',
+            'о' | 'ο' | 'σ' => 'o',
+            'р' | 'ρ' => 'p',
+            'ѕ' => 's',
+            'т' | 'τ' => 't',
+            'υ' | 'ս' => 'u',
+            'ν' | 'ѵ' => 'v',
+            'ԝ' | 'ω' => 'w',
+            'х' | 'χ' => 'x',
+            'у' | 'γ' => 'y',
+            c => c,
+        })
+        .collect::<String>()
+        .to_lowercase();
+
+    unconfused
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .replace("cl", "d")
+        .chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | 'i' | '|' => 'l',
+            c => c,
+        })
+        .collect()
+}
+
+/// Returns the known domain the given domain imitates, if any.
+fn imitated_domain(domain: &str) -> Option<&'static str> {
+    if KNOWN_DOMAINS
+        .iter()
+        .any(|known| is_subdomain_of(domain, known))
+    {
+        return None;
+    }
+
+    let (unicode_domain, _) = idna::domain_to_unicode(domain);
+    // only the registrable part matters, e.g. dlscord.com in login.dlscord.com
+    let registrable = unicode_domain
+        .rsplitn(3, '.')
+        .take(2)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect::<Vec<_>>()
+        .join(".");
+    let registrable = skeleton(&registrable);
+
+    KNOWN_DOMAINS
+        .iter()
+        .find(|known| skeleton(known) == registrable)
+        .copied()
+}
+
+/// Finds masked links (`[text](url)`) whose text is a link to a different domain than the target.
+fn check_masked_links(message: &str) -> Vec<Warning> {
+    let mut warnings = vec![];
+
+    let mut rest = message;
+    while let Some(start) = rest.find('[') {
+        rest = &rest[start + 1..];
+        let Some((text, after_text)) = rest.split_once("](") else {
+            break;
+        };
+        let Some((target, _)) = after_text.split_once(')') else {
+            break;
+        };
+        if text.contains('[') {
+            // the text is not the one of this masked link, try again from the next '['
+            continue;
+        }
+
+        let target = target.trim().trim_start_matches('<').trim_end_matches('>');
+        let text_domain = masked_text_domain(text);
+        let target_domain = Url::parse(target)
+            .ok()
+            .and_then(|url| url.domain().map(|domain| domain.to_string()));
+
+        if let (Some(text_domain), Some(target_domain)) = (text_domain, target_domain) {
+            let normalize = |domain: &str| domain.trim_start_matches("www.").to_string();
+            if normalize(&text_domain) != normalize(&target_domain) {
+                warnings.push(Warning::MaskedLinkMismatch {
+                    text: text.to_string(),
+                    target: target_domain,
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Returns the domain the text of a masked link looks like it points to, if it looks like a link.
+fn masked_text_domain(text: &str) -> Option<String> {
+    let text = text.trim().trim_start_matches('<').trim_end_matches('>');
+    if text.contains(char::is_whitespace) || !text.contains('.') {
+        return None;
+    }
+
+    Url::parse(text)
+        .or_else(|_| Url::parse(&format!("https://{text}")))
+        .ok()
+        .and_then(|url| url.domain().map(|domain| domain.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_message, Warning};
+
+    #[test]
+    fn test_check_message() {
+        let message = "
+            Free nitro!! https://dlscord.com/gift/abcdef and https://discorcl.gift/abcdef
+            https://discord.com/channels/1/2 is fine, so is https://cdn.discordapp.com/a.png
+            https://xn--dscord-pvf.com/login is not
+            [https://discord.com/gift](https://steamcornmunity.com/login)
+            [click here](https://example.com) and [example.com](https://www.example.com/page)
+            ";
+
+        let mut warnings = check_message(message);
+        warnings.sort_by_key(|warning| warning.to_string());
+
+        assert_eq!(
+            vec![
+                Warning::Lookalike {
+                    domain: "discorcl.gift".to_string(),
+                    imitates: "discord.gift".to_string(),
+                },
+                Warning::Lookalike {
+                    domain: "dlscord.com".to_string(),
+                    imitates: "discord.com".to_string(),
+                },
+                Warning::Lookalike {
+                    domain: "steamcornmunity.com".to_string(),
+                    imitates: "steamcommunity.com".to_string(),
+                },
+                Warning::Lookalike {
+                    domain: "xn--dscord-pvf.com".to_string(),
+                    imitates: "discord.com".to_string(),
+                },
+                Warning::MaskedLinkMismatch {
+                    text: "https://discord.com/gift".to_string(),
+                    target: "steamcornmunity.com".to_string(),
+                },
+            ],
+            warnings
+        )
+    }
+}
@@ -0,0 +1,190 @@
+// Settings changed at runtime through the admin commands, persisted to a JSON file
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, GuildId, UserId};
+
+use crate::{config::config, policy::Policy};
+
+/// What happens to messages with supported links in a guild, without anyone asking.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoMode {
+    #[default]
+    Off,
+    /// Repost the message with fixed links and delete the original
+    Delete,
+    /// Repost the message with fixed links and suppress the embeds of the original
+    Suppress,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    pub auto_mode: AutoMode,
+    /// Where to alert moderators about dangerous links sent in the guild
+    pub alert_channel: Option<ChannelId>,
+    /// Whether to suppress the embeds of messages that the bot replies to with better ones
+    pub suppress_embeds: bool,
+    pub policy: Policy,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// Frontends to show instead of the others, in order of preference
+    pub frontends: Vec<String>,
+    /// Whether auto mode leaves the user's messages alone
+    pub opt_out: bool,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Data {
+    guilds: HashMap<GuildId, GuildSettings>,
+    users: HashMap<UserId, UserSettings>,
+    /// How many times the settings were changed since they were loaded
+    #[serde(skip)]
+    version: u64,
+}
+
+pub struct Settings {
+    path: String,
+    data: Mutex<Data>,
+    /// The version of the settings in the file
+    saved_version: Arc<Mutex<u64>>,
+}
+
+impl Settings {
+    /// Loads the settings from the given file, starting afresh if it does not exist yet.
+    fn load(path: &str) -> Self {
+        let data = match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                panic!("Settings file {path} is not valid: {e}");
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Data::default(),
+            Err(e) => panic!("Failed to read settings file {path}: {e}"),
+        };
+
+        Self {
+            path: path.to_owned(),
+            data: Mutex::new(data),
+            saved_version: Arc::default(),
+        }
+    }
+
+    pub fn guild(&self, guild_id: GuildId) -> GuildSettings {
+        let data = self.data.lock().unwrap();
+        data.guilds.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    /// Changes the settings of a guild and saves them.
+    pub async fn update_guild(
+        &self,
+        guild_id: GuildId,
+        update: impl FnOnce(&mut GuildSettings),
+    ) -> io::Result<()> {
+        let (version, json) = {
+            let mut data = self.data.lock().unwrap();
+            update(data.guilds.entry(guild_id).or_default());
+            data.serialize_change()?
+        };
+        self.save(version, json).await
+    }
+
+    pub fn user(&self, user_id: UserId) -> UserSettings {
+        let data = self.data.lock().unwrap();
+        data.users.get(&user_id).cloned().unwrap_or_default()
+    }
+
+    /// Changes the settings of a user and saves them.
+    pub async fn update_user(
+        &self,
+        user_id: UserId,
+        update: impl FnOnce(&mut UserSettings),
+    ) -> io::Result<()> {
+        let (version, json) = {
+            let mut data = self.data.lock().unwrap();
+            update(data.users.entry(user_id).or_default());
+            data.serialize_change()?
+        };
+        self.save(version, json).await
+    }
+
+    /// Writes the given version of the settings to the file, away from the async runtime.
+    async fn save(&self, version: u64, json: String) -> io::Result<()> {
+        let path = self.path.clone();
+        let saved_version = self.saved_version.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut saved_version = saved_version.lock().unwrap();
+            // a later version has everything this one has, and must not be overwritten
+            if *saved_version >= version {
+                return Ok(());
+            }
+            // written to another file first, so that a failed write does not lose the settings
+            let tmp_path = format!("{path}.tmp");
+            fs::write(&tmp_path, json)?;
+            fs::rename(tmp_path, &path)?;
+            *saved_version = version;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+impl Data {
+    /// Counts a change to the settings, returning its version and the settings to save.
+    fn serialize_change(&mut self) -> io::Result<(u64, String)> {
+        self.version += 1;
+        Ok((self.version, serde_json::to_string_pretty(self)?))
+    }
+}
+
+/// The settings, which are loaded the first time they are needed. Loading them panics if the
+/// file cannot be read, so they are loaded at startup.
+pub fn settings() -> &'static Settings {
+    static SETTINGS: OnceLock<Settings> = OnceLock::new();
+    SETTINGS.get_or_init(|| Settings::load(&config().settings_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use serenity::all::UserId;
+
+    use super::Settings;
+
+    #[tokio::test]
+    async fn test_update_user() {
+        let path = env::temp_dir().join(format!("alt-urls-settings-{}.json", process::id()));
+        let path = path.to_str().unwrap();
+        let user = UserId::new(1);
+
+        let settings = Settings::load(path);
+        assert!(!settings.user(user).opt_out);
+        settings
+            .update_user(user, |user| {
+                user.opt_out = true;
+                user.frontends = vec!["piped".to_string()];
+            })
+            .await
+            .unwrap();
+        settings
+            .update_user(user, |user| user.frontends.clear())
+            .await
+            .unwrap();
+
+        // the settings are read back as they were last changed
+        let settings = Settings::load(path);
+        fs::remove_file(path).unwrap();
+        assert!(settings.user(user).opt_out);
+        assert!(settings.user(user).frontends.is_empty());
+        assert!(!settings.user(UserId::new(2)).opt_out);
+    }
+}